@@ -0,0 +1,53 @@
+use userprompt::{AsyncPrompting, Password};
+
+#[allow(dead_code)]
+#[derive(Debug, userprompt::AsyncPrompting)]
+enum TestEnum {
+    #[PromptComment = "This is the first option"]
+    Option1,
+    #[PromptComment = "This is a second option"]
+    Option2,
+    #[PromptComment = "This is an option"]
+    Option4(String),
+    Option5 {
+        #[PromptComment = "This is the first value representing nothing useful"]
+        asdf: u32,
+        #[PromptComment = "This is the second value representing stuff"]
+        fdsa: u64,
+    },
+}
+
+#[allow(dead_code)]
+#[derive(Debug, userprompt::AsyncPrompting)]
+struct TestMe {
+    #[PromptComment = "This is a plain vector of bytes"]
+    vec1: Vec<u8>,
+    #[PromptComment = "This is a simple test enum"]
+    e: TestEnum,
+    #[PromptComment = "This is an optional value ranging from 0-255"]
+    jim: Option<u8>,
+    #[PromptComment = "A map of numbers"]
+    map: std::collections::HashMap<String, u16>,
+    #[PromptComment = "This is the magic password"]
+    pw: Password,
+    #[PromptComment = "A new password"]
+    #[prompt(policy(min_length = 8))]
+    pw2: Option<userprompt::Password2>,
+}
+
+#[tokio::main]
+async fn main() {
+    println!("Running test program, press ctrl+c to stop");
+    let mut stdin = tokio::io::stdin();
+    let mut stdout = tokio::io::stdout();
+    tokio::select! {
+        s = TestMe::prompt_async(&mut stdin, &mut stdout, None, None) => {
+            if let Ok(s) = s {
+                println!("You entered {:?}", s);
+            }
+        }
+        _ = tokio::signal::ctrl_c() => {
+            println!("Cancelled");
+        }
+    }
+}
@@ -16,7 +16,7 @@ fn main() {
     .unwrap();
 }
 
-#[derive(Default, EguiPrompting)]
+#[derive(Clone, Default, EguiPrompting)]
 pub enum TestEnum {
     #[PromptComment = "Option 1 is the best"]
     #[default]
@@ -34,7 +34,7 @@ pub enum TestEnum {
     Option4(u8, u8),
 }
 
-#[derive(Default, EguiPrompting)]
+#[derive(Clone, Default, EguiPrompting)]
 pub struct Test2 {
     #[PromptComment = "Some number"]
     num: u8,
@@ -43,13 +43,19 @@ pub struct Test2 {
 }
 
 #[derive(Default, EguiPrompting)]
+#[prompt(layout = "grid")]
 pub struct Test {
     #[PromptComment = "map of strings"]
+    #[prompt(duplicate)]
     hm: userprompt::SelectedHashMap<String>,
     #[PromptComment = "map of things"]
     hm2: userprompt::SelectedHashMap<TestEnum>,
     #[PromptComment = "map of stuff"]
     hm3: userprompt::SelectedHashMap<Test2>,
+    #[PromptComment = "Ports for each service"]
+    ports: std::collections::HashMap<String, u16>,
+    #[PromptComment = "Names for each channel"]
+    channels: std::collections::BTreeMap<u8, String>,
     #[PromptComment = "Should we do the thing?"]
     booltest: bool,
     #[PromptComment = "What should the answer be?"]
@@ -64,7 +70,8 @@ pub struct Test {
     val_u16: u16,
     #[PromptComment = "A signed 2 byte variable"]
     val_i16: i16,
-    #[PromptComment = "An unsigned 4 byte variable"]
+    #[PromptComment = "A delay between 10 and 500 milliseconds"]
+    #[prompt(min = 10, max = 500, step = 10, unit = "ms")]
     val_u32: u32,
     #[PromptComment = "A signed 4 byte variable"]
     val_i32: i32,
@@ -76,23 +83,43 @@ pub struct Test {
     val_usize: usize,
     #[PromptComment = "a signed variable of optimal size"]
     val_isize: isize,
-    #[PromptComment = "A 32 bit float"]
+    #[PromptComment = "A percentage of at least 0"]
+    #[prompt(min = 0, unit = "%")]
     val_f32: f32,
     #[PromptComment = "A 64 bit float"]
     val_f64: f64,
+    #[PromptComment = "How long to wait"]
+    val_duration: std::time::Duration,
+    #[PromptComment = "The first day to run"]
+    val_date: chrono::NaiveDate,
+    #[PromptComment = "When to run next"]
+    val_datetime: chrono::NaiveDateTime,
+    #[PromptComment = "The server to connect to, port 443 is used if no port is given"]
+    #[prompt(default_port = 443)]
+    val_server: userprompt::HostPort,
     #[PromptComment = "A path to do stuff with"]
     val_pb: std::path::PathBuf,
-    #[PromptComment = "A file to create"]
+    #[PromptComment = "A text file to create, an existing one is replaced if you confirm it"]
+    #[prompt(overwrite = "ask", enforce_extension, filter("Text file", ["txt"]), title = "Save the text")]
     val_pb3: userprompt::FileCreate,
     #[PromptComment = "An optional file to create"]
     val_pb4: Option<userprompt::FileCreate>,
+    #[PromptComment = "The files to import"]
+    val_files: userprompt::FilesOpen,
+    #[PromptComment = "A directory to read from"]
+    val_dir: userprompt::DirectoryOpen,
+    #[PromptComment = "A directory to write output to"]
+    val_out: userprompt::DirectoryCreate,
     #[PromptComment = "Some data"]
+    #[prompt(max_len = 5, duplicate)]
     vec_u8: Vec<u8>,
     #[PromptComment = "Optional string"]
     optional: Option<String>,
     #[PromptComment = "Boxed string"]
+    #[allow(clippy::box_collection)]
     boxed_string: Box<String>,
     #[PromptComment = "The password to launch nuclear missiles"]
+    #[prompt(reveal)]
     pw: userprompt::Password,
     #[PromptComment = "An existing file to process"]
     val_pb2: userprompt::FileOpen,
@@ -101,12 +128,28 @@ pub struct Test {
     #[PromptComment = "An optional list of files to process"]
     complex1: Option<Vec<userprompt::FileOpen>>,
     #[PromptComment = "A new password to process stuff with"]
+    #[prompt(reveal, policy(min_length = 10, require_digit, require_symbol, reject_common))]
     pw2: userprompt::Password2,
+    #[PromptComment = "A password for the admin account, only the hash is kept"]
+    #[prompt(policy(min_length = 8))]
+    admin: userprompt::PasswordHash,
+}
+
+#[derive(Clone, Default, PartialEq, EguiPrompting)]
+pub struct Credentials {
+    #[PromptComment = "The name to log in with"]
+    user: String,
+    #[PromptComment = "The password for the user"]
+    password: userprompt::Password,
 }
 
 #[derive(Default)]
 struct MyEguiApp {
     test: Test,
+    /// True while the user is being asked to log in
+    logging_in: bool,
+    /// The user that logged in
+    user: Option<String>,
 }
 
 impl MyEguiApp {
@@ -128,8 +171,28 @@ impl eframe::App for MyEguiApp {
                 .auto_shrink([false; 2])
                 .show(ui, |ui| {
                     ui.heading("Hello World!");
-                    let _ = self.test.build_gui(ui, None, None);
+                    match &self.user {
+                        Some(user) => ui.label(format!("Logged in as {}", user)),
+                        None => ui.label("Not logged in"),
+                    };
+                    if ui.button("Log in").clicked() {
+                        self.logging_in = true;
+                    }
+                    let (_, errors) = userprompt::FormErrors::collect(ui, |ui| {
+                        self.test.build_gui(ui, None, None)
+                    });
+                    errors.show(ui);
                 });
         });
+        if self.logging_in {
+            match userprompt::egui::prompt_modal::<Credentials>(ctx, "Log in") {
+                Some(userprompt::FormEvent::Submitted(c)) => {
+                    self.user = Some(c.user);
+                    self.logging_in = false;
+                }
+                Some(userprompt::FormEvent::Cancelled) => self.logging_in = false,
+                None => {}
+            }
+        }
     }
 }
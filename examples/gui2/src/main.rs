@@ -3,7 +3,7 @@ pub mod egui_multiwin_dynamic {
     egui_multiwin::multi_window!(crate::AppCommon, crate::CustomEvent, crate::MyWindows);
 }
 
-use userprompt::EguiPrompting;
+use userprompt::{EguiPrompting, FormEvent, PromptForm};
 
 #[enum_dispatch(TrackedWindow)]
 pub enum MyWindows {
@@ -20,6 +20,7 @@ use egui_multiwin_dynamic::tracked_window::TrackedWindow;
 use std::sync::Arc;
 
 pub struct AppCommon {
+    #[allow(dead_code)]
     clicks: u32,
 }
 
@@ -35,7 +36,7 @@ impl CustomEvent {
     }
 }
 
-#[derive(Default, EguiPrompting)]
+#[derive(Clone, Default, PartialEq, EguiPrompting)]
 pub enum TestEnum {
     #[default]
     Option1,
@@ -47,7 +48,7 @@ pub enum TestEnum {
     Option4(u8, u8),
 }
 
-#[derive(Default, EguiPrompting)]
+#[derive(Clone, Default, PartialEq, EguiPrompting)]
 pub struct Test {
     booltest: bool,
     string: String,
@@ -68,6 +69,7 @@ pub struct Test {
     val_pb3: userprompt::FileCreate,
     vec_u8: Vec<u8>,
     optional: Option<String>,
+    #[allow(clippy::box_collection)]
     boxed_string: Box<String>,
     hm: userprompt::SelectedHashMap<String>,
     pw: userprompt::Password,
@@ -76,14 +78,14 @@ pub struct Test {
 }
 
 pub struct PopupWindow {
-    test: Test,
+    form: PromptForm<Test>,
 }
 
 impl PopupWindow {
     pub fn request() -> NewWindowRequest {
         NewWindowRequest {
             window_state: MyWindows::Popup(PopupWindow {
-                test: Test::default(),
+                form: PromptForm::new(Test::default()).with_name("test"),
             }),
             builder: egui_multiwin::winit::window::WindowBuilder::new()
                 .with_resizable(false)
@@ -108,27 +110,21 @@ impl TrackedWindow for PopupWindow {
 
     fn redraw(
         &mut self,
-        c: &mut AppCommon,
+        _c: &mut AppCommon,
         egui: &mut EguiGlow,
         _window: &egui_multiwin::winit::window::Window,
         _clipboard: &mut arboard::Clipboard,
     ) -> RedrawResponse {
-        let quit = false;
+        let mut quit = false;
         egui_multiwin::egui::CentralPanel::default().show(&egui.egui_ctx, |ui| {
-            self.test.val_pb2.title = Some("Test title".to_string());
-            self.test.val_pb2.filter = Some(("Test file".to_string(), vec!["*.txt".to_string()]));
-            egui_multiwin::egui::ScrollArea::vertical()
-                .auto_shrink([false; 2])
-                .show(ui, |ui| match self.test.build_gui(ui, Some("test"), None) {
-                    Ok(_) => {
-                        if ui.button("Submit").clicked() {
-                            println!("Clicked submit");
-                        }
-                    }
-                    Err(e) => {
-                        ui.label(format!("Failed to validate: {}", e));
-                    }
-                });
+            let test = self.form.value_mut();
+            test.val_pb2.title = Some("Test title".to_string());
+            test.val_pb2.filter = Some(("Test file".to_string(), vec!["*.txt".to_string()]));
+            match self.form.show(ui) {
+                Some(FormEvent::Submitted(_)) => println!("Clicked submit"),
+                Some(FormEvent::Cancelled) => quit = true,
+                None => {}
+            }
         });
         RedrawResponse {
             quit,
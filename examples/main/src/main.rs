@@ -23,27 +23,62 @@ enum TestEnum {
 #[derive(Debug, userprompt::Prompting)]
 struct TestMe {
     #[PromptComment = "This is a plain vector of bytes"]
+    #[prompt(min_len = 1, max_len = 3)]
     vec1: Vec<u8>,
     #[PromptComment = "This is a vector of test enums"]
     vec2: Vec<TestEnum>,
     #[PromptComment = "This is a vector of test2 enums"]
     vec3: Vec<TestMe2>,
+    #[PromptComment = "Names for each channel"]
+    channels: std::collections::BTreeMap<u8, String>,
     #[PromptComment = "This is a simple test enum"]
     e: TestEnum,
-    #[PromptComment = "This is a value ranging 0-255"]
+    #[PromptComment = "This is a value ranging 0-255, left at 0 if not entered within 30 seconds"]
+    #[prompt(timeout_secs = 30)]
     bob: u8,
     #[PromptComment = "This is an optional value ranging from 0-255"]
     jim: Option<u8>,
     #[PromptComment = "This is a struct for doing stuff"]
     asdf: TestMe2,
     #[PromptComment = "This is the magic password"]
+    #[prompt(mask = '*')]
     pw: Password,
-    #[PromptComment = "This is a path that may or may not exist"]
+    #[PromptComment = "How long to wait, such as 30s, 5m or 1h30m"]
+    delay: std::time::Duration,
+    #[PromptComment = "The server to connect to, port 8080 is used if no port is given"]
+    #[prompt(default_port = 8080)]
+    server: userprompt::HostPort,
+    #[PromptComment = "The address to listen on"]
+    listen: std::net::SocketAddr,
+    #[PromptComment = "The website to fetch"]
+    #[prompt(schemes("http", "https"))]
+    site: userprompt::url::Url,
+    #[PromptComment = "This is a path that may or may not exist, ~ and $VARIABLES are expanded"]
+    #[prompt(canonicalize)]
     path: std::path::PathBuf,
-    #[PromptComment = "Please enter a filename that exists"]
+    #[PromptComment = "Please enter a filename that exists and is not empty"]
+    #[prompt(validate = not_empty)]
     fo: userprompt::FileOpen,
     #[PromptComment = "Please enter a filename to crate"]
     fc: userprompt::FileCreate,
+    #[PromptComment = "Please enter a report file, an existing one is replaced if you confirm it"]
+    #[prompt(overwrite = "ask", enforce_extension, filter("Reports", ["txt"]), initial_file = "report.txt")]
+    report: userprompt::FileCreate,
+    #[PromptComment = "Please enter the files to import, such as data/*.csv"]
+    inputs: userprompt::FilesOpen,
+    #[PromptComment = "Please enter a directory that exists"]
+    dir: userprompt::DirectoryOpen,
+    #[PromptComment = "Please enter a directory to write output to"]
+    out: userprompt::DirectoryCreate,
+}
+
+/// Check that a file has some contents
+fn not_empty(p: &std::path::Path) -> Result<(), String> {
+    match std::fs::metadata(p) {
+        Ok(m) if m.len() > 0 => Ok(()),
+        Ok(_) => Err("The file is empty".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,59 @@
+use userprompt::tui::TuiPrompting;
+
+#[allow(dead_code)]
+#[derive(Debug, Default, TuiPrompting)]
+enum TestEnum {
+    #[PromptComment = "This is the first option"]
+    #[default]
+    Option1,
+    #[PromptComment = "This is a second option"]
+    Option2,
+    #[PromptComment = "This is an option"]
+    Option4(String),
+    Option5 {
+        #[PromptComment = "This is the first value representing nothing useful"]
+        asdf: u32,
+        #[PromptComment = "This is the second value representing stuff"]
+        fdsa: u64,
+    },
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Default, TuiPrompting)]
+struct TestMe2 {
+    #[PromptComment = "Please enter a size ranging from 0 - 255"]
+    size: u8,
+    #[PromptComment = "Please optionally enter a number ranging from 0 to 255"]
+    number: Option<u8>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Default, TuiPrompting)]
+struct TestMe {
+    #[PromptComment = "This is a plain vector of bytes"]
+    vec1: Vec<u8>,
+    #[PromptComment = "This is a vector of test enums"]
+    vec2: Vec<TestEnum>,
+    #[PromptComment = "This is a simple test enum"]
+    e: TestEnum,
+    #[PromptComment = "Should we do the thing?"]
+    booltest: bool,
+    #[PromptComment = "This is a struct for doing stuff"]
+    asdf: TestMe2,
+    #[PromptComment = "A map of numbers"]
+    map: std::collections::HashMap<String, u16>,
+    #[PromptComment = "This is the magic password"]
+    pw: userprompt::Password,
+    #[PromptComment = "A new password"]
+    #[prompt(policy(min_length = 8))]
+    pw2: userprompt::Password2,
+    #[PromptComment = "Please enter a filename that exists"]
+    fo: userprompt::FileOpen,
+}
+
+fn main() {
+    let s = userprompt::tui::prompt::<TestMe>(Some("Test form"), None);
+    if let Ok(s) = s {
+        println!("You entered {:?}", s);
+    }
+}
@@ -0,0 +1,894 @@
+//! Prompting for input over an asynchronous reader and writer, such as the tokio standard input and output or a socket.
+//!
+//! The futures returned hold no state outside of the reader and writer, so a prompt can be
+//! cancelled by dropping its future, for instance when racing it against a shutdown signal.
+
+use crate::Error;
+use std::future::Future;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// This trait is responsible for doing the work of prompting the user for input asynchronously.
+pub trait AsyncPrompting: Sized {
+    /// prompt for input of the specified type.
+    /// # Arguments
+    /// * reader - The source of input from the user
+    /// * writer - The destination of the prompts for the user
+    /// * name - The optional name to display for the type
+    /// * comment - The optional comment to show to the user to explain the field being entered
+    fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> impl Future<Output = Result<Self, Error>> + Send
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send;
+
+    /// prompt for input of the specified type, using options usually given by the prompt attribute of the derive macro.
+    /// Types that have no options prompt the same way as prompt_async.
+    fn prompt_async_with<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+        _options: &crate::PromptOptions,
+    ) -> impl Future<Output = Result<Self, Error>> + Send
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        Self::prompt_async(reader, writer, name, comment)
+    }
+}
+
+/// Write some text for the user
+pub async fn write_str<W>(writer: &mut W, s: &str) -> Result<(), Error>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    writer
+        .write_all(s.as_bytes())
+        .await
+        .map_err(Error::InputError)?;
+    writer.flush().await.map_err(Error::InputError)
+}
+
+/// Write a line of text for the user
+pub async fn write_line<W>(writer: &mut W, s: &str) -> Result<(), Error>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    write_str(writer, &format!("{}\n", s)).await
+}
+
+/// Read a line of text from the user, without the line ending.
+/// The line is read one byte at a time so that nothing past the end of the line is taken from the reader.
+pub async fn read_line<R>(reader: &mut R) -> Result<String, Error>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    let mut buffer = Vec::new();
+    read_bytes(reader, &mut buffer).await?;
+    String::from_utf8(buffer).map_err(|_| Error::ConversionError)
+}
+
+/// Read a line of secret text from the user, without the line ending, into memory that is erased when dropped.
+async fn read_secret<R>(reader: &mut R) -> Result<zeroize::Zeroizing<String>, Error>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    // Reserve space up front so that a long secret does not leave copies behind in reallocated memory
+    let mut buffer = zeroize::Zeroizing::new(Vec::with_capacity(256));
+    read_bytes(reader, &mut buffer).await?;
+    match String::from_utf8(std::mem::take(&mut *buffer)) {
+        Ok(s) => Ok(zeroize::Zeroizing::new(s)),
+        Err(e) => {
+            drop(zeroize::Zeroizing::new(e.into_bytes()));
+            Err(Error::ConversionError)
+        }
+    }
+}
+
+/// Read the bytes of a line into the buffer, removing the line ending
+async fn read_bytes<R>(reader: &mut R, buffer: &mut Vec<u8>) -> Result<(), Error>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    loop {
+        let mut b = [0u8; 1];
+        let n = reader.read(&mut b).await.map_err(Error::InputError)?;
+        if n == 0 {
+            if buffer.is_empty() {
+                return Err(Error::InputError(std::io::ErrorKind::UnexpectedEof.into()));
+            }
+            break;
+        }
+        if b[0] == b'\n' {
+            break;
+        }
+        buffer.push(b[0]);
+    }
+    while buffer.ends_with(b"\r") {
+        buffer.pop();
+    }
+    Ok(())
+}
+
+/// Prompt for a value that is parsed from a string, asking again until the input is valid
+pub async fn prompt_generic_async<T, R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    name: Option<&str>,
+    comment: Option<&str>,
+) -> Result<T, Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    if let Some(comment) = comment {
+        write_line(writer, comment).await?;
+    }
+    loop {
+        let v = String::prompt_async(reader, writer, name, None).await?;
+        match v.parse::<T>() {
+            Ok(v) => return Ok(v),
+            Err(e) => write_line(writer, &format!("Invalid input: {}", e)).await?,
+        }
+    }
+}
+
+/// Prompt for a value with a parse function that explains why the input is not valid, asking again until it is
+pub(crate) async fn prompt_parsed_async<T, R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    name: Option<&str>,
+    comment: Option<&str>,
+    parse: impl Fn(&str) -> Result<T, String> + Send,
+) -> Result<T, Error>
+where
+    T: Send,
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    if let Some(comment) = comment {
+        write_line(writer, comment).await?;
+    }
+    loop {
+        let s = String::prompt_async(reader, writer, name, None).await?;
+        match parse(s.trim()) {
+            Ok(v) => return Ok(v),
+            Err(e) => write_line(writer, &e).await?,
+        }
+    }
+}
+
+impl AsyncPrompting for String {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        _comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        if let Some(n) = name {
+            write_str(writer, &format!("{}: ", n)).await?;
+        }
+        read_line(reader).await
+    }
+}
+
+macro_rules! async_parsed {
+    ($($t:ty),*) => {
+        $(
+            impl AsyncPrompting for $t {
+                async fn prompt_async<R, W>(
+                    reader: &mut R,
+                    writer: &mut W,
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                ) -> Result<Self, Error>
+                where
+                    R: AsyncRead + Unpin + Send,
+                    W: AsyncWrite + Unpin + Send,
+                {
+                    prompt_generic_async(reader, writer, name, comment).await
+                }
+            }
+        )*
+    };
+}
+
+async_parsed!(
+    u8,
+    i8,
+    u16,
+    i16,
+    u32,
+    i32,
+    u64,
+    i64,
+    usize,
+    isize,
+    u128,
+    i128,
+    std::num::NonZeroU8,
+    std::num::NonZeroI8,
+    std::num::NonZeroU16,
+    std::num::NonZeroI16,
+    std::num::NonZeroU32,
+    std::num::NonZeroI32,
+    std::num::NonZeroU64,
+    std::num::NonZeroI64,
+    std::num::NonZeroU128,
+    std::num::NonZeroI128,
+    std::num::NonZeroUsize,
+    std::num::NonZeroIsize,
+    f32,
+    f64
+);
+
+/// A char is exactly one Unicode scalar value, which is not always a whole character as the user sees it.
+impl AsyncPrompting for char {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        if let Some(comment) = comment {
+            write_line(writer, comment).await?;
+        }
+        loop {
+            let s = String::prompt_async(reader, writer, name, None).await?;
+            match s.parse() {
+                Ok(c) => return Ok(c),
+                Err(_) => write_line(writer, &crate::char_error(&s)).await?,
+            }
+        }
+    }
+}
+
+impl AsyncPrompting for bool {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        if let Some(comment) = comment {
+            write_line(writer, comment).await?;
+        }
+        loop {
+            if let Some(n) = name {
+                write_str(writer, &format!("{} (yes,no,true,false): ", n)).await?;
+            }
+            let s = read_line(reader).await?;
+            match s.to_ascii_lowercase().as_str() {
+                "yes" | "true" => return Ok(true),
+                "no" | "false" => return Ok(false),
+                _ => write_line(writer, "Invalid input").await?,
+            }
+        }
+    }
+}
+
+/// A leading `~` and environment variables in the path are expanded, see [`crate::path::expand`].
+impl AsyncPrompting for std::path::PathBuf {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        let typed: String = prompt_generic_async(reader, writer, name, comment).await?;
+        Ok(crate::path::resolve(&typed, None, false))
+    }
+}
+
+impl AsyncPrompting for crate::FileOpen {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        loop {
+            let pb = std::path::PathBuf::prompt_async(reader, writer, name, comment).await?;
+            let f: crate::FileOpen = pb.into();
+            match f.check_path() {
+                Ok(()) => return Ok(f),
+                Err(e) => write_line(writer, &format!("{}, please try again", e)).await?,
+            }
+        }
+    }
+}
+
+impl AsyncPrompting for crate::FileCreate {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        loop {
+            let pb = std::path::PathBuf::prompt_async(reader, writer, name, comment).await?;
+            let f: crate::FileCreate = pb.into();
+            match f.check_path(f.overwrite) {
+                Ok(()) => return Ok(f),
+                Err(e) => write_line(writer, &format!("{}, please try again", e)).await?,
+            }
+        }
+    }
+}
+
+impl AsyncPrompting for crate::FilesOpen {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        loop {
+            let s = String::prompt_async(reader, writer, name, comment).await?;
+            match crate::FilesOpen::parse(&s) {
+                Ok(pbs) => return Ok(pbs.into()),
+                Err(e) => write_line(writer, &format!("{}, please try again", e)).await?,
+            }
+        }
+    }
+}
+
+impl AsyncPrompting for crate::DirectoryOpen {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        loop {
+            let pb = std::path::PathBuf::prompt_async(reader, writer, name, comment).await?;
+            match crate::check_directory_open(&pb) {
+                Ok(()) => return Ok(pb.into()),
+                Err(e) => write_line(writer, &format!("{}, please try again", e)).await?,
+            }
+        }
+    }
+}
+
+/// A directory that does not exist is created once the user confirms it.
+impl AsyncPrompting for crate::DirectoryCreate {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        loop {
+            let pb = std::path::PathBuf::prompt_async(reader, writer, name, comment).await?;
+            if let Err(e) = crate::check_directory_create(&pb) {
+                write_line(writer, &format!("{}, please try again", e)).await?;
+                continue;
+            }
+            let d: crate::DirectoryCreate = pb.into();
+            if d.is_dir() {
+                return Ok(d);
+            }
+            let create = bool::prompt_async(
+                reader,
+                writer,
+                Some("That directory does not exist, create it?"),
+                None,
+            )
+            .await?;
+            if create {
+                match d.create() {
+                    Ok(()) => return Ok(d),
+                    Err(e) => {
+                        let e =
+                            format!("Failed to create {}: {}, please try again", d.display(), e);
+                        write_line(writer, &e).await?
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The password is read as a plain line of text, since the reader may not be a terminal that can hide its input.
+impl AsyncPrompting for crate::Password {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        if let Some(comment) = comment {
+            write_line(writer, comment).await?;
+        }
+        if let Some(n) = name {
+            write_str(writer, &format!("{}: ", n)).await?;
+        }
+        let mut buffer = read_secret(reader).await?;
+        Ok(crate::Password::new(std::mem::take(&mut *buffer)))
+    }
+}
+
+/// The password is read as a plain line of text, since the reader may not be a terminal that can hide its input.
+impl AsyncPrompting for crate::Password2 {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        Self::prompt_async_with(
+            reader,
+            writer,
+            name,
+            comment,
+            &crate::PromptOptions::default(),
+        )
+        .await
+    }
+
+    async fn prompt_async_with<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        if let Some(comment) = comment {
+            write_line(writer, comment).await?;
+        }
+        let n = name.map(|n| format!("{}:", n)).unwrap_or_default();
+        loop {
+            write_str(writer, &format!("{}Enter password:", n)).await?;
+            let buffer = read_secret(reader).await?;
+            write_str(writer, &format!("{}Enter password again:", n)).await?;
+            let buf2 = read_secret(reader).await?;
+            if buffer != buf2 {
+                write_line(writer, "Passwords do not match, try again").await?;
+                continue;
+            }
+            match options.password_policy.as_ref().map(|p| p.check(&buffer)) {
+                Some(Err(reasons)) => {
+                    write_line(writer, "That password is not acceptable, try again").await?;
+                    for r in reasons {
+                        write_line(writer, &format!("\tThe password {}", r)).await?;
+                    }
+                }
+                _ => return Ok(crate::Password2(buffer.clone(), buffer)),
+            }
+        }
+    }
+}
+
+/// Prompt for the entries of a map, until the user enters a blank key
+async fn prompt_map<T, R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    name: Option<&str>,
+    comment: Option<&str>,
+) -> Result<std::collections::HashMap<String, T>, Error>
+where
+    T: AsyncPrompting + Send,
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    if let Some(comment) = comment {
+        write_line(writer, comment).await?;
+    }
+    if let Some(n) = name {
+        write_str(writer, &format!("{}: ", n)).await?;
+    }
+    let mut hm = std::collections::HashMap::new();
+    loop {
+        write_str(writer, "Enter key name (blank to end):").await?;
+        let key = read_line(reader).await?;
+        if key.is_empty() {
+            write_line(writer, "Done").await?;
+            break;
+        }
+        let t = T::prompt_async(reader, writer, None, None).await?;
+        hm.insert(key, t);
+    }
+    Ok(hm)
+}
+
+impl<T> AsyncPrompting for std::collections::HashMap<String, T>
+where
+    T: AsyncPrompting + Send,
+{
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        prompt_map(reader, writer, name, comment).await
+    }
+}
+
+impl<T> AsyncPrompting for crate::SelectedHashMap<T>
+where
+    T: AsyncPrompting + Send,
+{
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        let mut hm = crate::SelectedHashMap::new();
+        hm.map = prompt_map(reader, writer, name, comment).await?;
+        Ok(hm)
+    }
+}
+
+impl<T> AsyncPrompting for Vec<T>
+where
+    T: AsyncPrompting + Send,
+{
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        let mut built = Vec::new();
+        if let Some(comment) = comment {
+            write_line(writer, comment).await?;
+        }
+        if let Some(name) = name {
+            write_line(writer, &format!("Enter a list of items for {}", name)).await?;
+        }
+        loop {
+            let name2 = if let Some(n) = name {
+                format!("{}/element{}", n, built.len() + 1)
+            } else {
+                format!("element{}", built.len() + 1)
+            };
+            write_line(writer, "Provide an element? (yes/no)]").await?;
+            if !bool::prompt_async(reader, writer, None, None).await? {
+                break;
+            }
+            built.push(T::prompt_async(reader, writer, Some(&name2), None).await?);
+        }
+        Ok(built)
+    }
+}
+
+impl<T> AsyncPrompting for Option<T>
+where
+    T: AsyncPrompting + Send,
+{
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        if let Some(name) = name {
+            write_line(
+                writer,
+                &format!("[{} is optional, provide? (yes/no)]", name),
+            )
+            .await?;
+        }
+        if bool::prompt_async(reader, writer, name, None).await? {
+            T::prompt_async(reader, writer, name, comment)
+                .await
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn prompt_async_with<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        if let Some(name) = name {
+            write_line(
+                writer,
+                &format!("[{} is optional, provide? (yes/no)]", name),
+            )
+            .await?;
+        }
+        if bool::prompt_async(reader, writer, name, None).await? {
+            T::prompt_async_with(reader, writer, name, comment, options)
+                .await
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T> AsyncPrompting for Box<T>
+where
+    T: AsyncPrompting + Send,
+{
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        T::prompt_async(reader, writer, name, comment)
+            .await
+            .map(Box::new)
+    }
+
+    async fn prompt_async_with<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        T::prompt_async_with(reader, writer, name, comment, options)
+            .await
+            .map(Box::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Prompt for a value with the given input, returning the value and everything written to the user
+    async fn run<T: AsyncPrompting>(input: &str) -> (Result<T, Error>, String) {
+        let mut reader = input.as_bytes();
+        let mut writer = Vec::new();
+        let r = T::prompt_async(&mut reader, &mut writer, Some("value"), None).await;
+        (r, String::from_utf8(writer).unwrap())
+    }
+
+    #[tokio::test]
+    async fn read_line_removes_line_endings() {
+        let mut reader: &[u8] = b"first\r\nsecond\nthird\r\r\n";
+        assert_eq!(read_line(&mut reader).await.unwrap(), "first");
+        assert_eq!(read_line(&mut reader).await.unwrap(), "second");
+        assert_eq!(read_line(&mut reader).await.unwrap(), "third");
+    }
+
+    #[tokio::test]
+    async fn read_line_at_end_of_input() {
+        let mut reader: &[u8] = b"last";
+        assert_eq!(read_line(&mut reader).await.unwrap(), "last");
+        let r = read_line(&mut reader).await;
+        assert!(
+            matches!(r, Err(Error::InputError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+    }
+
+    #[tokio::test]
+    async fn read_line_keeps_the_rest_of_the_input() {
+        let mut reader: &[u8] = b"one\ntwo\n";
+        read_line(&mut reader).await.unwrap();
+        assert_eq!(reader, b"two\n");
+    }
+
+    #[tokio::test]
+    async fn read_line_multibyte() {
+        let (mut client, mut server) = tokio::io::duplex(4);
+        let writing = async {
+            client
+                .write_all("h\u{e9}llo \u{2713}\n".as_bytes())
+                .await
+                .unwrap();
+        };
+        let (_, line) = tokio::join!(writing, read_line(&mut server));
+        assert_eq!(line.unwrap(), "h\u{e9}llo \u{2713}");
+        let mut reader: &[u8] = b"\xff\xfe\n";
+        assert!(matches!(
+            read_line(&mut reader).await,
+            Err(Error::ConversionError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn parsed_asks_again_until_valid() {
+        let (r, out) = run::<u8>("300\nabc\n42\n").await;
+        assert_eq!(r.unwrap(), 42);
+        assert_eq!(out.matches("Invalid input: ").count(), 2);
+        assert_eq!(out.matches("value: ").count(), 3);
+    }
+
+    #[tokio::test]
+    async fn parsed_gives_up_at_end_of_input() {
+        let (r, _) = run::<u8>("abc\n").await;
+        assert!(matches!(r, Err(Error::InputError(_))));
+    }
+
+    #[tokio::test]
+    async fn address_explains_invalid_input() {
+        let (r, out) = run::<std::net::Ipv4Addr>("300.1.1.1\n10.0.0.1\n").await;
+        assert_eq!(r.unwrap(), std::net::Ipv4Addr::new(10, 0, 0, 1));
+        assert!(out.contains("Not a valid IPv4 address"));
+        let (r, out) = run::<crate::Hostname>("bad host\nexample.com\n").await;
+        assert_eq!(r.unwrap().to_string(), "example.com");
+        assert!(!out.contains("Invalid input"));
+    }
+
+    #[tokio::test]
+    async fn host_port_uses_default_port() {
+        let mut reader: &[u8] = b"example.com\n";
+        let mut writer = Vec::new();
+        let options = crate::PromptOptions {
+            default_port: Some(443),
+            ..Default::default()
+        };
+        let hp = crate::HostPort::prompt_async_with(&mut reader, &mut writer, None, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(hp.to_string(), "example.com:443");
+    }
+
+    #[tokio::test]
+    async fn char_is_one_scalar_value() {
+        let (r, out) = run::<char>("e\u{301}\n\n \n").await;
+        assert_eq!(r.unwrap(), ' ');
+        assert!(out.contains("Enter exactly one Unicode scalar value, 2 were entered"));
+        assert!(out.contains("Enter exactly one Unicode scalar value, 0 were entered"));
+        let (r, _) = run::<char>("\u{e9}\n").await;
+        assert_eq!(r.unwrap(), '\u{e9}');
+    }
+
+    #[tokio::test]
+    async fn vec_ends_when_declined() {
+        let (r, _) = run::<Vec<u8>>("yes\n1\ny\nyes\n2\nno\n").await;
+        assert_eq!(r.unwrap(), vec![1, 2]);
+        let (r, _) = run::<Vec<u8>>("no\n").await;
+        assert!(r.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn option_is_asked_for_first() {
+        let (r, _) = run::<Option<u8>>("no\n").await;
+        assert_eq!(r.unwrap(), None);
+        let (r, _) = run::<Option<u8>>("yes\n5\n").await;
+        assert_eq!(r.unwrap(), Some(5));
+    }
+
+    #[tokio::test]
+    async fn map_ends_at_blank_key() {
+        let (r, out) = run::<HashMap<String, u8>>("a\n1\nb\n2\n\n").await;
+        let map = r.unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["b"], 2);
+        assert!(out.ends_with("Done\n"));
+    }
+
+    #[tokio::test]
+    async fn password2_asks_again_when_different() {
+        let (r, out) = run::<crate::Password2>("one\ntwo\nthree\nthree\n").await;
+        assert_eq!(r.unwrap().expose_secret(), "three");
+        assert!(out.contains("Passwords do not match"));
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, PartialEq, crate::AsyncPrompting)]
+    enum Shape {
+        Point,
+        Circle(u8),
+        Rect { width: u8, height: u8 },
+    }
+
+    #[derive(Debug, PartialEq, crate::AsyncPrompting)]
+    struct Drawing {
+        name: String,
+        #[PromptComment = "The shapes in the drawing"]
+        shapes: Vec<Shape>,
+        scale: Option<u8>,
+        #[prompt(default_port = 80)]
+        server: crate::HostPort,
+    }
+
+    #[tokio::test]
+    async fn derive_prompts_for_each_field() {
+        let input = "sketch\nyes\nCircle\n3\nyes\nSquare\nRect\n4\n5\nno\nno\nlocalhost\n";
+        let (r, out) = run::<Drawing>(input).await;
+        let d = r.unwrap();
+        assert_eq!(d.name, "sketch");
+        assert_eq!(
+            d.shapes,
+            vec![
+                Shape::Circle(3),
+                Shape::Rect {
+                    width: 4,
+                    height: 5
+                }
+            ]
+        );
+        assert_eq!(d.scale, None);
+        assert_eq!(d.server.to_string(), "localhost:80");
+        assert!(out.contains("The shapes in the drawing"));
+        assert!(out.contains("Invalid option"));
+    }
+}
@@ -0,0 +1,58 @@
+//! The egui crate, along with a way to prompt for a value from inside a running egui app.
+//!
+//! [`prompt_modal`] shows a form for a value in a window that blocks the rest of the app until the
+//! form is submitted or cancelled. The value being entered is kept in the egui memory between frames,
+//! so the function only needs to be called each frame for as long as the value is wanted.
+
+pub use ::egui::*;
+
+use crate::{EguiPrompting, FormEvent, PromptForm};
+
+/// Show a form for a value in a modal window, returning an event once the user submits or cancels it.
+/// The id is used as the title of the window and to keep the value in the egui memory, where it is
+/// removed once an event is returned. Closing the window cancels it.
+pub fn prompt_modal<T>(ctx: &Context, id: &str) -> Option<FormEvent<T>>
+where
+    T: EguiPrompting + Clone + Default + PartialEq + Send + Sync + 'static,
+{
+    let title = id;
+    let id = Id::new(("userprompt modal", id));
+    let mut form = ctx
+        .data_mut(|d| d.remove_temp::<PromptForm<T>>(id))
+        .unwrap_or_default();
+
+    let screen = ctx.screen_rect();
+    let backdrop = Area::new(id.with("backdrop"))
+        .fixed_pos(screen.min)
+        .order(Order::Middle)
+        .interactable(true)
+        .show(ctx, |ui| {
+            ui.painter()
+                .rect_filled(screen, 0.0, Color32::from_black_alpha(96));
+            ui.allocate_rect(screen, Sense::click_and_drag());
+        });
+    ctx.move_to_top(backdrop.response.layer_id);
+
+    let mut open = true;
+    let mut event = None;
+    let window = Window::new(title)
+        .id(id)
+        .open(&mut open)
+        .collapsible(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.set_max_height(screen.height() * 0.8);
+            event = form.show(ui);
+        });
+    if let Some(w) = window {
+        ctx.move_to_top(w.response.layer_id);
+    }
+    if !open {
+        event = Some(FormEvent::Cancelled);
+    }
+
+    if event.is_none() {
+        ctx.data_mut(|d| d.insert_temp(id, form));
+    }
+    event
+}
@@ -0,0 +1,361 @@
+//! Egui forms, and showing the errors of their fields.
+//!
+//! Each field is shown with [`field`], which shows the error of the field in red under it and draws a
+//! red border around it, without stopping the rest of the form from being shown. Widgets that show
+//! their own errors use [`report_error`], so that the error is not shown twice. All of the errors of a
+//! form can be gathered with [`FormErrors::collect`] and listed with their field paths.
+//!
+//! Fields show their name and comment above them with [`labels`]. A grid layout instead shows the
+//! fields of a form in a single grid, each field with [`grid_row`], with the name beside it and the
+//! comment when hovering over the name, and fields that are forms of their own with [`grid_section`],
+//! beside the name in a section that can be collapsed.
+//!
+//! A [`PromptForm`] holds a value and shows it as a complete form, with buttons to submit, cancel,
+//! revert and reset it.
+
+use crate::EguiPrompting;
+
+/// The id of the errors being collected in the egui memory
+fn collector_id() -> egui::Id {
+    egui::Id::new("userprompt form errors")
+}
+
+/// The id of the number of errors reported in the egui memory
+fn count_id() -> egui::Id {
+    egui::Id::new("userprompt form error count")
+}
+
+/// The id of the flag in the egui memory that stops the field shown in a Ui from showing its own labels.
+/// The flag is kept for the Ui made by [`without_labels`], so it is not seen by the fields of other Uis.
+fn hide_labels_id(ui: &egui::Ui) -> egui::Id {
+    ui.id().with("userprompt hide labels")
+}
+
+/// Show the comment and name of a field above it.
+/// Returns false without showing anything when the layout already shows them, see [`without_labels`].
+pub fn labels(ui: &mut egui::Ui, name: Option<&str>, comment: Option<&str>) -> bool {
+    let id = hide_labels_id(ui);
+    if ui.data_mut(|d| d.remove_temp::<bool>(id)).unwrap_or(false) {
+        return false;
+    }
+    if let Some(comment) = comment {
+        ui.label(comment);
+    }
+    if let Some(n) = name {
+        ui.label(n);
+    }
+    true
+}
+
+/// Show a field whose name and comment are shown by the layout, so that it does not show its own labels.
+/// The field is shown in a Ui of its own, and only a call to [`labels`] with that Ui is affected.
+pub fn without_labels<R>(ui: &mut egui::Ui, f: impl FnOnce(&mut egui::Ui) -> R) -> R {
+    ui.scope(|ui| {
+        let id = hide_labels_id(ui);
+        ui.data_mut(|d| d.insert_temp(id, true));
+        let r = f(ui);
+        ui.data_mut(|d| d.remove::<bool>(id));
+        r
+    })
+    .inner
+}
+
+/// Show a field as a row of a grid layout, with its label in the first column and its comment shown when hovering over the label.
+/// This is called inside an `egui::Grid` with two columns, and the caller ends the row.
+pub fn grid_row(
+    ui: &mut egui::Ui,
+    path: &str,
+    label: &str,
+    comment: Option<&str>,
+    f: impl FnOnce(&mut egui::Ui) -> Result<(), String>,
+) -> Result<(), String> {
+    let l = ui.label(label);
+    if let Some(c) = comment {
+        l.on_hover_text(c);
+    }
+    ui.vertical(|ui| field(ui, path, |ui| without_labels(ui, f)))
+        .inner
+}
+
+/// Show a field that is a form of its own as a row of a grid layout, with its label in the first column
+/// and the form in a section that can be collapsed in the second. Like [`grid_row`] this is called inside
+/// an `egui::Grid`, and a collapsed section is not shown, so the errors in it are only found by checking the object.
+pub fn grid_section(
+    ui: &mut egui::Ui,
+    path: &str,
+    label: &str,
+    comment: Option<&str>,
+    f: impl FnOnce(&mut egui::Ui) -> Result<(), String>,
+) -> Result<(), String> {
+    let l = ui.label(label);
+    if let Some(c) = comment {
+        l.on_hover_text(c);
+    }
+    let mut r = Ok(());
+    ui.vertical(|ui| {
+        egui::CollapsingHeader::new("")
+            .id_source(("section", path))
+            .default_open(true)
+            .show(ui, |ui| {
+                r = field(ui, path, |ui| without_labels(ui, f));
+            });
+    });
+    r
+}
+
+/// Show a field that is a form of its own in a section that can be collapsed, with its label as the heading
+/// and its comment shown when hovering over the heading. A collapsed section is not shown, so the errors
+/// in it are only found by checking the object.
+pub fn section(
+    ui: &mut egui::Ui,
+    path: &str,
+    label: &str,
+    comment: Option<&str>,
+    f: impl FnOnce(&mut egui::Ui) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut r = Ok(());
+    let c = egui::CollapsingHeader::new(label)
+        .id_source(("section", path))
+        .default_open(true)
+        .show(ui, |ui| {
+            r = field(ui, path, |ui| without_labels(ui, f));
+        });
+    if let Some(comment) = comment {
+        c.header_response.on_hover_text(comment);
+    }
+    r
+}
+
+/// The number of errors reported so far
+fn error_count(ui: &egui::Ui) -> u64 {
+    ui.data(|d| d.get_temp(count_id())).unwrap_or_default()
+}
+
+/// An error found in a field of a form
+#[derive(Clone, Debug)]
+pub struct FieldError {
+    /// The path of the field, such as `/server/port`
+    pub path: String,
+    /// The reason the field is not valid
+    pub message: String,
+}
+
+/// Show an error for a field in red, and add it to the errors being collected for the form.
+/// The path is removed from the start of the message, as checks usually begin their errors with it.
+pub fn report_error(ui: &mut egui::Ui, path: &str, message: &str) {
+    let message = match message.strip_prefix(path) {
+        Some(m) if !path.is_empty() => m.trim_start(),
+        _ => message,
+    };
+    ui.colored_label(ui.visuals().error_fg_color, message);
+    ui.data_mut(|d| {
+        *d.get_temp_mut_or_default::<u64>(count_id()) += 1;
+        if let Some(mut errors) = d.get_temp::<Vec<FieldError>>(collector_id()) {
+            errors.push(FieldError {
+                path: path.to_string(),
+                message: message.to_string(),
+            });
+            d.insert_temp(collector_id(), errors);
+        }
+    });
+}
+
+/// Show a field of a form. If the field returns an error, a red border is drawn around it, and the error
+/// is reported under it unless the field already reported errors of its own.
+pub fn field(
+    ui: &mut egui::Ui,
+    path: &str,
+    f: impl FnOnce(&mut egui::Ui) -> Result<(), String>,
+) -> Result<(), String> {
+    let before = error_count(ui);
+    let r = ui.scope(f);
+    if let Err(e) = &r.inner {
+        let color = ui.visuals().error_fg_color;
+        ui.painter().rect_stroke(
+            r.response.rect.expand(2.0),
+            2.0,
+            egui::Stroke::new(1.0, color),
+        );
+        if error_count(ui) == before {
+            report_error(ui, path, e);
+        }
+    }
+    r.inner
+}
+
+/// The errors of the fields of a form
+#[derive(Clone, Debug, Default)]
+pub struct FormErrors {
+    /// The errors in the order the fields were shown
+    errors: Vec<FieldError>,
+}
+
+impl FormErrors {
+    /// Show a form, collecting the errors of all of its fields
+    pub fn collect<R>(ui: &mut egui::Ui, f: impl FnOnce(&mut egui::Ui) -> R) -> (R, Self) {
+        let outer = ui.data(|d| d.get_temp::<Vec<FieldError>>(collector_id()));
+        ui.data_mut(|d| d.insert_temp(collector_id(), Vec::<FieldError>::new()));
+        let r = f(ui);
+        let errors = ui
+            .data(|d| d.get_temp::<Vec<FieldError>>(collector_id()))
+            .unwrap_or_default();
+        ui.data_mut(|d| match outer {
+            Some(mut outer) => {
+                outer.extend(errors.iter().cloned());
+                d.insert_temp(collector_id(), outer);
+            }
+            None => d.remove::<Vec<FieldError>>(collector_id()),
+        });
+        (r, Self { errors })
+    }
+
+    /// Returns true if no errors were found
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The errors that were found
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Show a list of the errors with the paths of their fields
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let color = ui.visuals().error_fg_color;
+        for e in &self.errors {
+            ui.colored_label(color, format!("{}: {}", e.path, e.message));
+        }
+    }
+}
+
+/// What the user did with a [`PromptForm`]
+#[derive(Clone, Debug)]
+pub enum FormEvent<T> {
+    /// The form was submitted with a valid value
+    Submitted(T),
+    /// The form was cancelled
+    Cancelled,
+}
+
+/// A form for a value, shown in a scroll area with buttons to submit, cancel, revert and reset it.
+/// Submit is only enabled when the form has no errors and the value passes its check.
+///
+/// The form has unsaved changes when the value differs from the value it was created with or last submitted.
+/// Values are compared with PartialEq, or with the function given to [`PromptForm::with_comparison`].
+#[derive(Clone)]
+pub struct PromptForm<T> {
+    /// The value being edited
+    value: T,
+    /// The value when the form was created or last submitted
+    original: T,
+    /// Returns true when two values are the same
+    same: fn(&T, &T) -> bool,
+    /// The name given to the value
+    name: Option<String>,
+}
+
+impl<T: EguiPrompting + Clone + Default + PartialEq> Default for PromptForm<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: EguiPrompting + Clone + Default + PartialEq> PromptForm<T> {
+    /// Construct a form for a value
+    pub fn new(value: T) -> Self {
+        Self::with_comparison(value, T::eq)
+    }
+}
+
+impl<T: EguiPrompting + Clone + Default> PromptForm<T> {
+    /// Construct a form for a value that is compared with a function to find unsaved changes,
+    /// for values that do not implement PartialEq or that should be compared differently
+    pub fn with_comparison(value: T, same: fn(&T, &T) -> bool) -> Self {
+        Self {
+            original: value.clone(),
+            value,
+            same,
+            name: None,
+        }
+    }
+
+    /// Give the value a name, which is shown at the top of the form and used in the paths of its fields
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Get the value being edited
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Get the value being edited, changes made through this are unsaved changes like those made in the form
+    pub fn value_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Get the value being edited, consuming the form
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Returns true if the value has changed since the form was created or last submitted
+    pub fn is_dirty(&self) -> bool {
+        !(self.same)(&self.value, &self.original)
+    }
+
+    /// Go back to the value from when the form was created or last submitted
+    pub fn revert(&mut self) {
+        self.value = self.original.clone();
+    }
+
+    /// Replace the value with the default value
+    pub fn reset(&mut self) {
+        self.value = T::default();
+    }
+
+    /// Show the form, returning an event when the user submits or cancels it
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<FormEvent<T>> {
+        let name = self.name.as_deref();
+        let buttons_height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y * 2.0;
+        let value = &mut self.value;
+        let output = egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .max_height((ui.available_height() - buttons_height).max(0.0))
+            .show(ui, |ui| {
+                let (_, errors) = FormErrors::collect(ui, |ui| value.build_gui(ui, name, None));
+                errors
+            });
+        let errors = output.inner;
+
+        let check = self.value.check(name);
+        let valid = errors.is_empty() && check.is_ok();
+        if errors.is_empty() {
+            if let Err(e) = &check {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+            }
+        }
+        let mut event = None;
+        let dirty = self.is_dirty();
+        ui.horizontal(|ui| {
+            if ui.add_enabled(valid, egui::Button::new("Submit")).clicked() {
+                self.original = self.value.clone();
+                event = Some(FormEvent::Submitted(self.value.clone()));
+            }
+            if ui.button("Cancel").clicked() {
+                event = Some(FormEvent::Cancelled);
+            }
+            if ui.add_enabled(dirty, egui::Button::new("Revert")).clicked() {
+                self.revert();
+            }
+            if ui.button("Reset to default").clicked() {
+                self.reset();
+            }
+            if dirty {
+                ui.label("Unsaved changes");
+            }
+        });
+        event
+    }
+}
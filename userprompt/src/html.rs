@@ -0,0 +1,1283 @@
+//! A backend that presents a form to the user as an html page served on a local port.
+//!
+//! Each request is handled in two passes over the value being edited. The first pass applies the
+//! submitted form values, the second pass performs any structural action (adding or removing an
+//! element of a list) and renders the page.
+
+pub use userprompt_derive::HtmlPrompting;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+
+/// The trait involved in building an html form for a type
+pub trait HtmlPrompting: Sized {
+    /// Apply any values submitted through the form and render the form elements for the object
+    /// # Arguments
+    /// * form - The form being built
+    /// * name - The optional name of the object, also used as the key for the form elements
+    /// * comment - The optional comment to show to the user to explain the field being entered
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String>;
+
+    /// Perform any additional checks required in order to check the object for validness
+    fn check(&self, name: Option<&str>) -> Result<(), String>;
+
+    /// Apply any submitted values and render the form elements, using options usually given by the
+    /// prompt attribute of the derive macro. Types that have no options build the same form as build_html.
+    fn build_html_with(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+        _options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        self.build_html(form, name, comment)
+    }
+
+    /// Check the object, using options usually given by the prompt attribute of the derive macro.
+    fn check_with(
+        &self,
+        name: Option<&str>,
+        _options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        self.check(name)
+    }
+}
+
+/// The html form being built, along with the values submitted by the user
+#[derive(Default)]
+pub struct HtmlForm {
+    /// The values submitted by the user, keyed by the name of the form element
+    values: HashMap<String, String>,
+    /// The structural action to perform while rendering
+    action: Option<String>,
+    /// Elements that failed to parse or validate, with the text the user entered and the reason
+    invalid: HashMap<String, (Option<String>, String)>,
+    /// Values from the first pass that are needed to perform the structural action
+    carried: HashMap<String, String>,
+    /// True when check failures should be shown next to each element
+    show_checks: bool,
+    /// The html generated for the form elements
+    html: String,
+}
+
+impl HtmlForm {
+    /// Construct a new form with no submitted values
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The key used for the form element of an object with the given name
+    pub fn key(name: Option<&str>) -> String {
+        name.unwrap_or("value").to_string()
+    }
+
+    /// Get the value submitted for the given key, if any
+    pub fn submitted(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|s| s.as_str())
+    }
+
+    /// Returns true and consumes the action if it is the pending structural action
+    pub fn take_action(&mut self, action: &str) -> bool {
+        if self.action.as_deref() == Some(action) {
+            self.action = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Mark a form element as invalid, optionally keeping the text the user entered so it can be shown again
+    pub fn invalid(&mut self, key: &str, raw: Option<String>, reason: String) {
+        self.invalid.insert(key.to_string(), (raw, reason));
+    }
+
+    /// Report the result of checking an object, the error is shown next to its element once the user has tried to submit
+    pub fn report(&mut self, key: &str, r: Result<(), String>) -> Result<(), String> {
+        if let Err(e) = &r {
+            if self.show_checks && !self.invalid.contains_key(key) {
+                self.invalid(key, None, e.clone());
+            }
+        }
+        r
+    }
+
+    /// Returns the list of elements that are invalid along with the reason
+    pub fn errors(&self) -> Vec<(&str, &str)> {
+        let mut e: Vec<(&str, &str)> = self
+            .invalid
+            .iter()
+            .map(|(k, (_, r))| (k.as_str(), r.as_str()))
+            .collect();
+        e.sort();
+        e
+    }
+
+    /// Get the html generated so far
+    pub fn html(&self) -> &str {
+        &self.html
+    }
+
+    /// Write the optional label and comment for an element
+    fn label(&mut self, key: &str, name: Option<&str>, comment: Option<&str>) {
+        if let Some(comment) = comment {
+            self.html
+                .push_str(&format!("<div class=\"comment\">{}</div>", escape(comment)));
+        }
+        if let Some(n) = name {
+            self.html.push_str(&format!(
+                "<label for=\"{}\">{}</label>",
+                escape(key),
+                escape(short_name(n))
+            ));
+        }
+    }
+
+    /// Write the error for an element, if there is one
+    fn error(&mut self, key: &str) {
+        if let Some((_, reason)) = self.invalid.get(key) {
+            let e = format!("<div class=\"error\">{}</div>", escape(reason));
+            self.html.push_str(&e);
+        }
+    }
+
+    /// Add an input element, kind is the html input type
+    pub fn input(
+        &mut self,
+        key: &str,
+        name: Option<&str>,
+        comment: Option<&str>,
+        kind: &str,
+        value: &str,
+    ) {
+        self.html.push_str("<div class=\"field\">");
+        self.label(key, name, comment);
+        let value = match self.invalid.get(key) {
+            Some((Some(raw), _)) => raw.as_str(),
+            _ => value,
+        };
+        let class = if self.invalid.contains_key(key) {
+            " class=\"invalid\""
+        } else {
+            ""
+        };
+        self.html.push_str(&format!(
+            "<input type=\"{}\" id=\"{k}\" name=\"{k}\" value=\"{}\"{}>",
+            kind,
+            escape(value),
+            class,
+            k = escape(key)
+        ));
+        self.error(key);
+        self.html.push_str("</div>");
+    }
+
+    /// Add a password element, the current value is never sent back to the browser
+    pub fn password(&mut self, key: &str, name: Option<&str>, comment: Option<&str>, set: bool) {
+        self.html.push_str("<div class=\"field\">");
+        self.label(key, name, comment);
+        let placeholder = if set { "(unchanged)" } else { "" };
+        self.html.push_str(&format!(
+            "<input type=\"password\" id=\"{k}\" name=\"{k}\" placeholder=\"{}\">",
+            placeholder,
+            k = escape(key)
+        ));
+        self.error(key);
+        self.html.push_str("</div>");
+    }
+
+    /// Add a checkbox element
+    pub fn checkbox(
+        &mut self,
+        key: &str,
+        name: Option<&str>,
+        comment: Option<&str>,
+        checked: bool,
+    ) {
+        self.html.push_str("<div class=\"field\">");
+        if let Some(comment) = comment {
+            self.html
+                .push_str(&format!("<div class=\"comment\">{}</div>", escape(comment)));
+        }
+        // The hidden element makes sure a value is submitted when the box is not checked
+        self.html.push_str(&format!(
+            "<input type=\"hidden\" name=\"{k}\" value=\"false\"><input type=\"checkbox\" id=\"{k}\" name=\"{k}\" value=\"true\"{}>",
+            if checked { " checked" } else { "" },
+            k = escape(key)
+        ));
+        self.html.push_str(&format!(
+            "<label for=\"{}\">{}</label>",
+            escape(key),
+            escape(short_name(name.unwrap_or("Item")))
+        ));
+        self.error(key);
+        self.html.push_str("</div>");
+    }
+
+    /// Add a selection element, returns the new selection when the user changes it
+    pub fn choice(
+        &mut self,
+        key: &str,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &[&str],
+        current: &str,
+    ) -> Option<String> {
+        let selected = self
+            .submitted(key)
+            .filter(|s| *s != current && options.contains(s))
+            .map(|s| s.to_string());
+        let shown = selected.clone().unwrap_or_else(|| current.to_string());
+        self.select(key, name, comment, options, &shown);
+        selected
+    }
+
+    /// Add a selection element, changing the selection immediately updates the form
+    fn select(
+        &mut self,
+        key: &str,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &[&str],
+        selected: &str,
+    ) {
+        self.html.push_str("<div class=\"field\">");
+        self.label(key, name, comment);
+        self.html.push_str(&format!(
+            "<select id=\"{k}\" name=\"{k}\" onchange=\"this.form.submit()\">",
+            k = escape(key)
+        ));
+        for o in options {
+            self.html.push_str(&format!(
+                "<option{}>{}</option>",
+                if *o == selected { " selected" } else { "" },
+                escape(o)
+            ));
+        }
+        self.html.push_str("</select>");
+        self.error(key);
+        self.html.push_str("</div>");
+    }
+
+    /// Add a button that performs the given structural action
+    pub fn button(&mut self, action: &str, text: &str) {
+        self.html.push_str(&format!(
+            "<button type=\"submit\" name=\"__action\" value=\"{}\">{}</button>",
+            escape(action),
+            escape(text)
+        ));
+    }
+
+    /// Add some plain text
+    pub fn text(&mut self, text: &str) {
+        self.html.push_str(&format!("<p>{}</p>", escape(text)));
+    }
+
+    /// Start a group of elements
+    pub fn begin_group(&mut self, name: Option<&str>, comment: Option<&str>) {
+        self.html.push_str("<fieldset>");
+        if let Some(n) = name {
+            self.html
+                .push_str(&format!("<legend>{}</legend>", escape(short_name(n))));
+        }
+        if let Some(comment) = comment {
+            self.html
+                .push_str(&format!("<div class=\"comment\">{}</div>", escape(comment)));
+        }
+    }
+
+    /// End a group of elements
+    pub fn end_group(&mut self) {
+        self.html.push_str("</fieldset>");
+    }
+
+    /// Run the two passes over the object for one request
+    fn process<T: HtmlPrompting>(
+        &mut self,
+        value: &mut T,
+        values: HashMap<String, String>,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.invalid.clear();
+        self.carried.clear();
+        let action = values.get("__action").cloned();
+        self.values = values;
+        self.html.clear();
+        let _ = value.build_html(self, name, comment);
+        self.values.clear();
+        self.action = action;
+        self.html.clear();
+        let r = value.build_html(self, name, comment);
+        self.action = None;
+        r.and(value.check(name))
+    }
+}
+
+/// Get the last portion of a slash separated name
+fn short_name(name: &str) -> &str {
+    let n = name.trim_end_matches('/');
+    match n.rsplit('/').next() {
+        Some(s) if !s.is_empty() => s,
+        _ => name,
+    }
+}
+
+/// Escape text for inclusion in html
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Decode a percent encoded component of a urlencoded form
+fn url_decode(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        match b[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < b.len() => {
+                let hex = |c: u8| (c as char).to_digit(16);
+                match (hex(b[i + 1]), hex(b[i + 2])) {
+                    (Some(h), Some(l)) => {
+                        out.push((h * 16 + l) as u8);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Parse the body of an application/x-www-form-urlencoded request. Later values replace earlier values with the same name.
+fn parse_form(body: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for pair in body.split('&').filter(|p| !p.is_empty()) {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        map.insert(url_decode(k), url_decode(v));
+    }
+    map
+}
+
+/// Apply a submitted value that is parsed from a string, then render it as an input element
+fn build_parsed<T>(
+    v: &mut T,
+    form: &mut HtmlForm,
+    name: Option<&str>,
+    comment: Option<&str>,
+) -> Result<(), String>
+where
+    T: std::str::FromStr + std::fmt::Display,
+{
+    let key = HtmlForm::key(name);
+    if let Some(s) = form.submitted(&key).map(|s| s.to_string()) {
+        match s.trim().parse::<T>() {
+            Ok(val) => *v = val,
+            Err(_) => {
+                let reason = format!("{} is not a valid value", s);
+                form.invalid(&key, Some(s), reason);
+            }
+        }
+    }
+    form.input(&key, name, comment, "text", &v.to_string());
+    match form.invalid.get(&key) {
+        Some((_, reason)) => Err(reason.clone()),
+        None => Ok(()),
+    }
+}
+
+macro_rules! html_parsed {
+    ($($t:ty),*) => {
+        $(
+            impl HtmlPrompting for $t {
+                fn build_html(
+                    &mut self,
+                    form: &mut HtmlForm,
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                ) -> Result<(), String> {
+                    build_parsed(self, form, name, comment)
+                }
+
+                fn check(&self, _name: Option<&str>) -> Result<(), String> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+html_parsed!(
+    u8,
+    i8,
+    u16,
+    i16,
+    u32,
+    i32,
+    u64,
+    i64,
+    usize,
+    isize,
+    f32,
+    f64,
+    u128,
+    i128,
+    char,
+    std::num::NonZeroU8,
+    std::num::NonZeroI8,
+    std::num::NonZeroU16,
+    std::num::NonZeroI16,
+    std::num::NonZeroU32,
+    std::num::NonZeroI32,
+    std::num::NonZeroU64,
+    std::num::NonZeroI64,
+    std::num::NonZeroU128,
+    std::num::NonZeroI128,
+    std::num::NonZeroUsize,
+    std::num::NonZeroIsize,
+    std::net::IpAddr,
+    std::net::Ipv4Addr,
+    std::net::Ipv6Addr,
+    std::net::SocketAddr,
+    crate::Hostname,
+    crate::HostPort
+);
+
+impl HtmlPrompting for String {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        if let Some(s) = form.submitted(&key) {
+            *self = s.to_string();
+        }
+        form.input(&key, name, comment, "text", self);
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl HtmlPrompting for bool {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        if let Some(s) = form.submitted(&key) {
+            *self = s == "true";
+        }
+        form.checkbox(&key, name, comment, *self);
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl HtmlPrompting for std::path::PathBuf {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        if let Some(s) = form.submitted(&key) {
+            *self = std::path::PathBuf::from(s);
+        }
+        form.input(&key, name, comment, "text", &self.display().to_string());
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl HtmlPrompting for crate::FileOpen {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.pb.build_html(form, name, comment)?;
+        let r = self.check(name);
+        form.report(&HtmlForm::key(name), r)
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        self.check_path()
+    }
+}
+
+impl HtmlPrompting for crate::FileCreate {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let before = self.pb.clone();
+        self.pb.build_html(form, name, comment)?;
+        if self.pb != before {
+            self.confirmed = false;
+        }
+        if self.overwrite == crate::Overwrite::Ask && self.pb.is_file() {
+            let subname = format!("{}/replace", name.unwrap_or(""));
+            self.confirmed
+                .build_html(form, Some(&subname), Some("Replace the existing file"))?;
+        }
+        let r = self.check(name);
+        form.report(&HtmlForm::key(name), r)
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        self.check_path(self.overwrite)
+    }
+}
+
+impl HtmlPrompting for crate::FilesOpen {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        if let Some(s) = form.submitted(&key).map(|s| s.to_string()) {
+            match crate::FilesOpen::parse(&s) {
+                Ok(pbs) => self.pbs = pbs,
+                Err(e) => form.invalid(&key, Some(s), e),
+            }
+        }
+        form.input(&key, name, comment, "text", &self.to_line());
+        match form.invalid.get(&key) {
+            Some((_, reason)) => Err(reason.clone()),
+            None => {
+                let r = self.check(name);
+                form.report(&key, r)
+            }
+        }
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        self.check_files()
+    }
+}
+
+impl HtmlPrompting for crate::DirectoryOpen {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.pb.build_html(form, name, comment)?;
+        let r = self.check(name);
+        form.report(&HtmlForm::key(name), r)
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        crate::check_directory_open(&self.pb)
+    }
+}
+
+impl HtmlPrompting for crate::DirectoryCreate {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.pb.build_html(form, name, comment)?;
+        let r = self.check(name);
+        form.report(&HtmlForm::key(name), r)
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        crate::check_directory_create(&self.pb)
+    }
+}
+
+impl HtmlPrompting for crate::Password {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        if let Some(s) = form.submitted(&key) {
+            if !s.is_empty() {
+                self.0 = s.to_string().into();
+            }
+        }
+        form.password(&key, name, comment, !self.0.is_empty());
+        let r = self.check(name);
+        form.report(&key, r)
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        if self.0.is_empty() {
+            return Err(format!("{} password is blank", name.unwrap_or("")));
+        }
+        Ok(())
+    }
+}
+
+impl HtmlPrompting for crate::Password2 {
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_html_with(form, name, comment, &crate::PromptOptions::default())
+    }
+
+    fn build_html_with(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        let key2 = format!("{}/again", key);
+        if let Some(s) = form.submitted(&key) {
+            if !s.is_empty() {
+                self.0 = s.to_string().into();
+            }
+        }
+        if let Some(s) = form.submitted(&key2) {
+            if !s.is_empty() {
+                self.1 = s.to_string().into();
+            }
+        }
+        form.password(&key, name, comment, !self.0.is_empty());
+        form.password(
+            &key2,
+            Some("Enter password again"),
+            None,
+            !self.1.is_empty(),
+        );
+        let r = self.check_with(name, options);
+        form.report(&key, r)
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        if !self.0.is_empty() && self.0 == self.1 {
+            Ok(())
+        } else {
+            Err(format!("{} password does not match", name.unwrap_or("")))
+        }
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &crate::PromptOptions) -> Result<(), String> {
+        self.check(name)?;
+        self.check_options(name, options)
+    }
+}
+
+impl<T> HtmlPrompting for Vec<T>
+where
+    T: HtmlPrompting + Default,
+{
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        if form.take_action(&format!("add:{}", key)) {
+            self.push(T::default());
+        }
+        if let Some(i) =
+            (0..self.len()).find(|i| form.take_action(&format!("remove:{}/{}", key, i + 1)))
+        {
+            self.remove(i);
+        }
+        form.begin_group(name, comment);
+        let mut r = Ok(());
+        for (i, e) in self.iter_mut().enumerate() {
+            let name2 = format!("{}/{}", key, i + 1);
+            let r2 = e.build_html(form, Some(&name2), None);
+            if r.is_ok() {
+                r = r2;
+            }
+            form.button(&format!("remove:{}", name2), "Remove");
+        }
+        form.button(&format!("add:{}", key), "Add another");
+        form.end_group();
+        r
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        for i in self {
+            i.check(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> HtmlPrompting for Option<T>
+where
+    T: HtmlPrompting + Default,
+{
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_html_with(form, name, comment, &crate::PromptOptions::default())
+    }
+
+    fn build_html_with(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        let key = format!("{}/provided", HtmlForm::key(name));
+        let mut checked = self.is_some();
+        if let Some(s) = form.submitted(&key) {
+            checked = s == "true";
+        }
+        if checked != self.is_some() {
+            *self = if checked { Some(T::default()) } else { None };
+        }
+        form.checkbox(&key, name, None, checked);
+        match self {
+            Some(thing) => thing.build_html_with(form, name, comment, options),
+            None => Ok(()),
+        }
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        self.check_with(name, &crate::PromptOptions::default())
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &crate::PromptOptions) -> Result<(), String> {
+        match self {
+            Some(thing) => thing.check_with(name, options),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> HtmlPrompting for Box<T>
+where
+    T: HtmlPrompting,
+{
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.as_mut().build_html(form, name, comment)
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        self.as_ref().check(name)
+    }
+
+    fn build_html_with(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        self.as_mut().build_html_with(form, name, comment, options)
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &crate::PromptOptions) -> Result<(), String> {
+        self.as_ref().check_with(name, options)
+    }
+}
+
+/// Builds the editor for the entries of a map keyed by strings
+fn build_map<T>(map: &mut HashMap<String, T>, form: &mut HtmlForm, key: &str) -> Result<(), String>
+where
+    T: HtmlPrompting + Default,
+{
+    let new_key = format!("{}/new", key);
+    if form.take_action(&format!("add:{}", key)) {
+        if let Some(k) = form.carried.remove(&new_key) {
+            if !k.is_empty() && !map.contains_key(&k) {
+                map.insert(k, T::default());
+            }
+        }
+    }
+    if let Some(k) = map
+        .keys()
+        .find(|k| form.action.as_deref() == Some(&format!("remove:{}/{}", key, k)))
+        .cloned()
+    {
+        form.action = None;
+        map.remove(&k);
+    }
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    let mut r = Ok(());
+    for k in keys {
+        let name2 = format!("{}/{}", key, k);
+        if let Some(e) = map.get_mut(&k) {
+            let r2 = e.build_html(form, Some(&name2), None);
+            if r.is_ok() {
+                r = r2;
+            }
+        }
+        form.button(&format!("remove:{}", name2), "Remove");
+    }
+    if let Some(s) = form.submitted(&new_key).map(|s| s.to_string()) {
+        form.carried.insert(new_key.clone(), s);
+    }
+    form.html.push_str("<div class=\"field\">");
+    form.html.push_str(&format!(
+        "<label for=\"{k}\">Name for new entry</label><input type=\"text\" id=\"{k}\" name=\"{k}\">",
+        k = escape(&new_key)
+    ));
+    form.html.push_str("</div>");
+    form.button(&format!("add:{}", key), "Add new entry");
+    r
+}
+
+impl<T> HtmlPrompting for HashMap<String, T>
+where
+    T: HtmlPrompting + Default,
+{
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        form.begin_group(name, comment);
+        let r = build_map(self, form, &key);
+        form.end_group();
+        r
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        for a in self.values() {
+            a.check(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> HtmlPrompting for crate::SelectedHashMap<T>
+where
+    T: HtmlPrompting + Default,
+{
+    fn build_html(
+        &mut self,
+        form: &mut HtmlForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = HtmlForm::key(name);
+        form.begin_group(name, comment);
+        let r = build_map(&mut self.map, form, &key);
+        let mut keys = self.keys();
+        keys.insert(0, "");
+        let current = self.selected().unwrap_or_default().to_string();
+        let skey = format!("{}/selection", key);
+        if let Some(s) = form.choice(&skey, Some("Selected entry"), None, &keys, &current) {
+            if !self.select(&s) {
+                self.deselect();
+            }
+        }
+        form.end_group();
+        r
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        for a in self.map.values() {
+            a.check(name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The style sheet used for the form
+const STYLE: &str = "body{font-family:sans-serif;max-width:50em;margin:auto}\
+fieldset{margin:0.5em 0}\
+.field{margin:0.4em 0}\
+.field label{margin:0 0.5em}\
+.comment{color:#555;font-size:0.9em}\
+.error{color:#b00}\
+input.invalid{border:2px solid #b00}\
+.default{position:absolute;left:-9999px}";
+
+/// The longest time to wait for the browser to send more of a request before dropping the connection
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// The largest request line and headers that are accepted
+const MAX_HEADER_SIZE: u64 = 16 * 1024;
+
+/// The largest request body that is accepted
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Serves an html form on a local port and waits for the user to submit it.
+///
+/// Requests must name a localhost address as their host, so that other web sites cannot reach the
+/// form through their own domain names. A submitted form must also carry the token that is placed
+/// in the form served to the user, so that other web pages cannot submit it.
+pub struct FormServer {
+    /// The listener accepting connections from the browser
+    listener: std::net::TcpListener,
+    /// The title of the page
+    title: String,
+    /// The token that a submitted form must carry
+    token: String,
+    /// True when requests from other machines are allowed
+    remote: bool,
+}
+
+impl FormServer {
+    /// Listen on the given localhost address, use port 0 to pick any free port.
+    /// Addresses that are not loopback addresses are refused, see [`FormServer::bind_remote`].
+    pub fn bind<A: std::net::ToSocketAddrs>(addr: A) -> Result<Self, crate::Error> {
+        let addrs: Vec<_> = addr
+            .to_socket_addrs()
+            .map_err(crate::Error::InputError)?
+            .collect();
+        if let Some(a) = addrs.iter().find(|a| !a.ip().is_loopback()) {
+            return Err(crate::Error::InputError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a loopback address", a),
+            )));
+        }
+        Self::bind_remote(&addrs[..])
+    }
+
+    /// Listen on the given address, which may be reachable from other machines.
+    /// Anyone that can reach the address can fill out the form.
+    pub fn bind_remote<A: std::net::ToSocketAddrs>(addr: A) -> Result<Self, crate::Error> {
+        let listener = std::net::TcpListener::bind(addr).map_err(crate::Error::InputError)?;
+        let remote = !listener_is_local(&listener);
+        let mut token = [0u8; 16];
+        getrandom::getrandom(&mut token).map_err(|e| crate::Error::InputError(e.into()))?;
+        Ok(Self {
+            listener,
+            title: "Form".to_string(),
+            token: token.iter().map(|b| format!("{:02x}", b)).collect(),
+            remote,
+        })
+    }
+
+    /// Set the title of the page
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// The address that the form is served on
+    pub fn local_addr(&self) -> Result<std::net::SocketAddr, crate::Error> {
+        self.listener.local_addr().map_err(crate::Error::InputError)
+    }
+
+    /// Serve the form for a default value of the type until the user submits a valid form
+    pub fn prompt<T>(&self, name: Option<&str>, comment: Option<&str>) -> Result<T, crate::Error>
+    where
+        T: HtmlPrompting + Default,
+    {
+        self.prompt_value(T::default(), name, comment)
+    }
+
+    /// Serve the form for the given value until the user submits a valid form
+    pub fn prompt_value<T>(
+        &self,
+        mut value: T,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<T, crate::Error>
+    where
+        T: HtmlPrompting,
+    {
+        let mut form = HtmlForm::new();
+        for stream in self.listener.incoming() {
+            let mut stream = stream.map_err(crate::Error::InputError)?;
+            let request = match read_request(&mut stream) {
+                Ok(r) => r,
+                Err(RequestError::TooLarge) => {
+                    let _ = respond(&mut stream, "413 Payload Too Large", "Too large");
+                    continue;
+                }
+                Err(RequestError::Io) => continue,
+            };
+            if !self.remote && !request.header("host").is_some_and(is_local_host) {
+                let _ = respond(&mut stream, "403 Forbidden", "Forbidden");
+                continue;
+            }
+            if request.path != "/" {
+                let _ = respond(&mut stream, "404 Not Found", "Not found");
+                continue;
+            }
+            let values = if request.method == "POST" {
+                let values = parse_form(&request.body);
+                if !self.same_origin(&request)
+                    || values.get("__token").map(|s| s.as_str()) != Some(self.token.as_str())
+                {
+                    let _ = respond(&mut stream, "403 Forbidden", "Forbidden");
+                    continue;
+                }
+                values
+            } else {
+                HashMap::new()
+            };
+            let submit = values.get("__action").map(|s| s.as_str()) == Some("submit");
+            if submit {
+                form.show_checks = true;
+            }
+            let r = form.process(&mut value, values, name, comment);
+            if submit && r.is_ok() && form.invalid.is_empty() {
+                let page =
+                    self.page("<p>The form has been submitted, this page can be closed.</p>");
+                let _ = respond(&mut stream, "200 OK", &page);
+                return Ok(value);
+            }
+            let mut body = format!(
+                "<form method=\"post\"><input type=\"hidden\" name=\"__token\" value=\"{}\">",
+                self.token
+            );
+            // Pressing enter in a text field uses the first button, which should only update the form
+            body.push_str("<button type=\"submit\" class=\"default\" name=\"__action\" value=\"update\" tabindex=\"-1\">Update</button>");
+            if form.show_checks {
+                if let Err(e) = &r {
+                    let mut errors = form.errors();
+                    if errors.is_empty() {
+                        errors.push(("", e.as_str()));
+                    }
+                    body.push_str("<div class=\"error\"><ul>");
+                    for (k, e) in errors {
+                        body.push_str(&format!("<li>{} {}</li>", escape(k), escape(e)));
+                    }
+                    body.push_str("</ul></div>");
+                }
+            }
+            body.push_str(form.html());
+            body.push_str("<div><button type=\"submit\" name=\"__action\" value=\"update\">Update</button><button type=\"submit\" name=\"__action\" value=\"submit\">Submit</button></div></form>");
+            let page = self.page(&body);
+            let _ = respond(&mut stream, "200 OK", &page);
+        }
+        Err(crate::Error::InputError(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "listener closed",
+        )))
+    }
+
+    /// Returns true if a request did not come from a page of another web site.
+    /// Browsers send the origin of the page with a form they submit.
+    fn same_origin(&self, request: &Request) -> bool {
+        match (request.header("origin"), request.header("host")) {
+            (None, _) => true,
+            (Some(origin), Some(host)) => origin == format!("http://{}", host),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Build a complete html page with the given body
+    fn page(&self, body: &str) -> String {
+        format!(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{t}</title><style>{}</style></head><body><h1>{t}</h1>{}</body></html>",
+            STYLE,
+            body,
+            t = escape(&self.title)
+        )
+    }
+}
+
+/// Returns true if the listener only accepts connections from this machine
+fn listener_is_local(listener: &std::net::TcpListener) -> bool {
+    listener.local_addr().is_ok_and(|a| a.ip().is_loopback())
+}
+
+/// Returns true if the host header of a request names this machine
+fn is_local_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((n, port)) if !port.contains(']') => n,
+        _ => host,
+    };
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+    name.eq_ignore_ascii_case("localhost")
+        || name
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// An http request from the browser
+struct Request {
+    /// The method of the request
+    method: String,
+    /// The path requested
+    path: String,
+    /// The headers of the request, with lowercase names
+    headers: HashMap<String, String>,
+    /// The body of the request
+    body: String,
+}
+
+impl Request {
+    /// Get the value of a header, the name must be lowercase
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(|s| s.as_str())
+    }
+}
+
+/// The reasons a request could not be read
+enum RequestError {
+    /// The connection failed or stalled
+    Io,
+    /// The request is larger than allowed
+    TooLarge,
+}
+
+impl From<std::io::Error> for RequestError {
+    fn from(_: std::io::Error) -> Self {
+        RequestError::Io
+    }
+}
+
+/// Read an http request. Connections that stall are dropped, so that one idle connection cannot
+/// stop the form from being served.
+fn read_request(stream: &mut std::net::TcpStream) -> Result<Request, RequestError> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut head = (&mut reader).take(MAX_HEADER_SIZE);
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+    if !line.ends_with('\n') {
+        if head.limit() == 0 {
+            return Err(RequestError::TooLarge);
+        }
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        if head.read_line(&mut header)? == 0 {
+            if head.limit() == 0 {
+                return Err(RequestError::TooLarge);
+            }
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = header.split_once(':') {
+            headers.insert(k.trim().to_ascii_lowercase(), v.trim().to_string());
+        }
+    }
+    let length = match headers.get("content-length") {
+        Some(l) => l.parse().map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "bad content length")
+        })?,
+        None => 0,
+    };
+    if length > MAX_BODY_SIZE {
+        return Err(RequestError::TooLarge);
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+/// Write an http response
+fn respond(stream: &mut std::net::TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_decode_handles_escapes() {
+        assert_eq!(url_decode("a+b%20c"), "a b c");
+        assert_eq!(url_decode("%2Fpath%2fx"), "/path/x");
+        assert_eq!(url_decode("%e2%9c%93"), "\u{2713}");
+        assert_eq!(url_decode("100%"), "100%");
+        assert_eq!(url_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn parse_form_splits_pairs() {
+        let form = parse_form("a=1&b=x+y&&c&a=2&%2Fname%2Fx=%3D");
+        assert_eq!(form.get("a").map(|s| s.as_str()), Some("2"));
+        assert_eq!(form.get("b").map(|s| s.as_str()), Some("x y"));
+        assert_eq!(form.get("c").map(|s| s.as_str()), Some(""));
+        assert_eq!(form.get("/name/x").map(|s| s.as_str()), Some("="));
+        assert_eq!(form.len(), 4);
+    }
+
+    #[test]
+    fn local_hosts() {
+        assert!(is_local_host("localhost:8080"));
+        assert!(is_local_host("127.0.0.1:8080"));
+        assert!(is_local_host("127.0.0.1"));
+        assert!(is_local_host("[::1]:8080"));
+        assert!(is_local_host("[::1]"));
+        assert!(!is_local_host("example.com:8080"));
+        assert!(!is_local_host("192.168.1.2:8080"));
+        assert!(!is_local_host("localhost.example.com"));
+    }
+
+    #[test]
+    fn bind_refuses_other_addresses() {
+        assert!(FormServer::bind("0.0.0.0:0").is_err());
+        assert!(FormServer::bind("127.0.0.1:0").is_ok());
+    }
+}
@@ -2,6 +2,96 @@
 
 pub use userprompt_derive::Prompting;
 
+// Lets the tests use the derive macros, which refer to this crate by name
+#[cfg(test)]
+extern crate self as userprompt;
+
+pub mod timeout;
+
+pub use timeout::{prompt_or_default, prompt_with_timeout, set_timeout};
+
+pub mod password;
+
+pub use password::PasswordPolicy;
+
+#[cfg(feature = "hash")]
+pub use password::PasswordHash;
+
+pub mod time;
+
+pub mod net;
+
+pub mod path;
+
+pub use net::{HostPort, Hostname};
+
+#[cfg(feature = "egui")]
+pub mod form;
+
+#[cfg(feature = "egui")]
+pub use form::{FormErrors, FormEvent, PromptForm};
+
+#[cfg(feature = "html")]
+pub mod html;
+
+#[cfg(feature = "tui")]
+pub mod tui;
+
+#[cfg(feature = "async")]
+pub mod asynchronous;
+
+#[cfg(feature = "async")]
+pub use asynchronous::AsyncPrompting;
+
+#[cfg(feature = "async")]
+pub use userprompt_derive::AsyncPrompting;
+
+#[cfg(feature = "async")]
+pub use tokio;
+
+#[cfg(feature = "url")]
+pub use url;
+
+/// The function that checks a file for a FileValidator
+type FileCheck = dyn Fn(&std::path::Path) -> Result<(), String> + Send + Sync;
+
+/// A check of the contents of a file, such as that it parses as a certificate, returning the reason the file is not acceptable
+#[derive(Clone)]
+pub struct FileValidator(std::sync::Arc<FileCheck>);
+
+impl FileValidator {
+    /// Construct a validator from a function
+    pub fn new(f: impl Fn(&std::path::Path) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        Self(std::sync::Arc::new(f))
+    }
+
+    /// Check a file
+    pub fn validate(&self, pb: &std::path::Path) -> Result<(), String> {
+        (self.0)(pb)
+    }
+}
+
+impl std::fmt::Debug for FileValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FileValidator")
+    }
+}
+
+/// Returns true if the path has one of the extensions of a file dialog filter
+fn has_filter_extension(pb: &std::path::Path, exts: &[String]) -> bool {
+    pb.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+        exts.iter()
+            .any(|x| x == "*" || x.trim_start_matches("*.").eq_ignore_ascii_case(e))
+    })
+}
+
+/// Replace a setting with the one given in the options, if any
+fn apply_option<T: Clone>(setting: &mut Option<T>, option: &Option<T>) {
+    if option.is_some() {
+        *setting = option.clone();
+    }
+}
+
 /// This is used to open existing files on the filesystem
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -20,6 +110,78 @@ pub struct FileOpen {
     /// The file dialog title
     #[cfg_attr(feature = "serde", serde(skip))]
     pub title: Option<String>,
+    /// Require the file to have one of the extensions of the filter
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub enforce_extension: bool,
+    /// The check of the contents of the file
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub validator: Option<FileValidator>,
+    /// The path last checked by the validator in a graphical form, and the result
+    #[cfg_attr(feature = "serde", serde(skip))]
+    validated: Option<(std::path::PathBuf, Result<(), String>)>,
+    /// The path as it was typed on the command line, before it was expanded
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    typed: Option<String>,
+}
+
+impl FileOpen {
+    /// The path as it was typed on the command line, before it was expanded, None if it was not typed
+    pub fn typed(&self) -> Option<&str> {
+        self.typed.as_deref()
+    }
+
+    /// Check that the file is a readable regular file with an allowed extension, and that the validator accepts it
+    pub(crate) fn check_path(&self) -> Result<(), String> {
+        if self.pb.as_os_str().is_empty() {
+            return Err("No file selected".to_string());
+        }
+        if !self.pb.exists() {
+            return Err("Selected file does not exist".to_string());
+        }
+        if !self.pb.is_file() {
+            return Err(format!("Selected path {} is not a file", self.pb.display()));
+        }
+        if let (true, Some((_, exts))) = (self.enforce_extension, &self.filter) {
+            if !has_filter_extension(&self.pb, exts) {
+                let exts: Vec<&str> = exts.iter().map(|e| e.trim_start_matches("*.")).collect();
+                return Err(format!(
+                    "Selected file {} must have one of the extensions: {}",
+                    self.pb.display(),
+                    exts.join(", ")
+                ));
+            }
+        }
+        if let Err(e) = std::fs::File::open(&self.pb) {
+            return Err(format!("Selected file {} cannot be read: {}", self.pb.display(), e));
+        }
+        match &self.validated {
+            Some((pb, r)) if *pb == self.pb => r.clone(),
+            _ => match &self.validator {
+                Some(v) => v.validate(&self.pb),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Use the dialog settings and checks given in the options
+    pub(crate) fn apply_options(&mut self, options: &PromptOptions) {
+        apply_option(&mut self.filter, &options.filter);
+        apply_option(&mut self.initial_dir, &options.initial_dir);
+        apply_option(&mut self.initial_file, &options.initial_file);
+        apply_option(&mut self.title, &options.title);
+        apply_option(&mut self.validator, &options.validator);
+        self.enforce_extension |= options.enforce_extension;
+    }
+
+    /// Run the validator if the path has changed since it was last run
+    #[cfg(feature = "egui")]
+    fn revalidate(&mut self) {
+        if self.validated.as_ref().map(|(pb, _)| pb) != Some(&self.pb) {
+            self.validated = None;
+            let r = self.check_path();
+            self.validated = Some((self.pb.clone(), r));
+        }
+    }
 }
 
 impl From<std::path::PathBuf> for FileOpen {
@@ -30,6 +192,10 @@ impl From<std::path::PathBuf> for FileOpen {
             initial_dir: None,
             initial_file: None,
             title: None,
+            enforce_extension: false,
+            validator: None,
+            validated: None,
+            typed: None,
         }
     }
 }
@@ -47,6 +213,18 @@ impl std::ops::DerefMut for FileOpen {
     }
 }
 
+/// What to do when the file selected to be created already exists
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overwrite {
+    /// The file must not exist
+    #[default]
+    Reject,
+    /// The user must confirm that the existing file is replaced
+    Ask,
+    /// The existing file is replaced
+    Allow,
+}
+
 /// This is used to create new files on the filesystem
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -62,6 +240,126 @@ pub struct FileCreate {
     /// The file dialog title
     #[cfg_attr(feature = "serde", serde(skip))]
     pub title: Option<String>,
+    /// What to do when the selected file already exists
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub overwrite: Overwrite,
+    /// Add the first extension of the filter to a selected file that has none of its extensions
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub enforce_extension: bool,
+    /// The user has confirmed that the existing file should be replaced
+    #[cfg_attr(feature = "serde", serde(skip))]
+    confirmed: bool,
+    /// The directory last checked for writing in a graphical form, and the result
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dir_checked: Option<(std::path::PathBuf, Result<(), String>)>,
+    /// The path as it was typed on the command line, before it was expanded
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    typed: Option<String>,
+}
+
+impl FileCreate {
+    /// The path as it was typed on the command line, before it was expanded, None if it was not typed
+    pub fn typed(&self) -> Option<&str> {
+        self.typed.as_deref()
+    }
+
+    /// Add the first extension of the filter when enforce_extension is set and the file has none of the extensions of the filter
+    pub fn apply_extension(&mut self) {
+        let ext = match &self.filter {
+            Some((_, exts)) if self.enforce_extension && !self.pb.as_os_str().is_empty() => exts,
+            _ => return,
+        };
+        if let (false, Some(first)) = (has_filter_extension(&self.pb, ext), ext.first()) {
+            let mut s = std::mem::take(&mut self.pb).into_os_string();
+            s.push(".");
+            s.push(first.trim_start_matches("*."));
+            self.pb = s.into();
+        }
+    }
+
+    /// Use the dialog settings and checks given in the options
+    pub(crate) fn apply_options(&mut self, options: &PromptOptions) {
+        apply_option(&mut self.filter, &options.filter);
+        apply_option(&mut self.initial_dir, &options.initial_dir);
+        apply_option(&mut self.title, &options.title);
+        if let Some(o) = options.overwrite {
+            self.overwrite = o;
+        }
+        self.enforce_extension |= options.enforce_extension;
+    }
+
+    /// Check that the file can be written in its directory, following the overwrite policy if it already exists
+    pub(crate) fn check_path(&self, overwrite: Overwrite) -> Result<(), String> {
+        if self.pb.as_os_str().is_empty() {
+            return Err("No file selected".to_string());
+        }
+        if self.pb.is_dir() {
+            return Err(format!("Selected path {} is a directory", self.pb.display()));
+        }
+        if self.pb.exists() {
+            match overwrite {
+                Overwrite::Reject => {
+                    return Err(format!("Selected file {} already exists", self.pb.display()))
+                }
+                Overwrite::Ask if !self.confirmed => {
+                    return Err(format!(
+                        "Selected file {} already exists, confirm that it should be replaced",
+                        self.pb.display()
+                    ))
+                }
+                _ => {}
+            }
+            if let Err(e) = std::fs::OpenOptions::new().write(true).open(&self.pb) {
+                return Err(format!("Selected file {} cannot be written: {}", self.pb.display(), e));
+            }
+        }
+        let parent = self.dir();
+        if !parent.is_dir() {
+            return Err(format!("The directory {} does not exist", parent.display()));
+        }
+        match &self.dir_checked {
+            Some((dir, r)) if dir == parent => r.clone(),
+            _ => check_writable_dir(parent),
+        }
+    }
+
+    /// The directory the file is created in
+    fn dir(&self) -> &std::path::Path {
+        match self.pb.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => std::path::Path::new("."),
+        }
+    }
+
+    /// Check that the directory is writable if the path has moved to another directory since it was last checked
+    #[cfg(feature = "egui")]
+    fn recheck_dir(&mut self) {
+        if self.dir_checked.as_ref().map(|(dir, _)| dir.as_path()) != Some(self.dir()) {
+            let dir = self.dir().to_path_buf();
+            let r = check_writable_dir(&dir);
+            self.dir_checked = Some((dir, r));
+        }
+    }
+}
+
+/// Check that files can be created in a directory by creating an empty file in it and removing it again
+fn check_writable_dir(dir: &std::path::Path) -> Result<(), String> {
+    static TRIALS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    loop {
+        let n = TRIALS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let trial = dir.join(format!(".userprompt-{}-{}", std::process::id(), n));
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&trial) {
+            Ok(f) => {
+                drop(f);
+                let _ = std::fs::remove_file(&trial);
+                return Ok(());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(format!("The directory {} is not writable: {}", dir.display(), e))
+            }
+        }
+    }
 }
 
 impl From<std::path::PathBuf> for FileCreate {
@@ -71,6 +369,11 @@ impl From<std::path::PathBuf> for FileCreate {
             filter: None,
             initial_dir: None,
             title: None,
+            overwrite: Overwrite::Reject,
+            enforce_extension: false,
+            confirmed: false,
+            dir_checked: None,
+            typed: None,
         }
     }
 }
@@ -88,6 +391,315 @@ impl std::ops::DerefMut for FileCreate {
     }
 }
 
+/// This is used to open several existing files on the filesystem.
+/// On the command line the files are separated by spaces or commas, with quotes around any path containing either,
+/// and glob patterns such as `data/*.csv` are expanded to the files that match.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilesOpen {
+    /// The pathbufs
+    pbs: Vec<std::path::PathBuf>,
+    /// The optional filter for the open dialog
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub filter: Option<(String, Vec<String>)>,
+    /// The initial directory
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub initial_dir: Option<std::path::PathBuf>,
+    /// The file dialog title
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub title: Option<String>,
+}
+
+impl FilesOpen {
+    /// Parse a list of paths and glob patterns, returning the files in the order given.
+    /// Every path must be an existing file, and every pattern must match at least one file.
+    /// A path containing glob characters is taken as it is when a file with that name exists.
+    pub fn parse(s: &str) -> Result<Vec<std::path::PathBuf>, String> {
+        Self::parse_in(s, None)
+    }
+
+    /// Parse a list of paths and glob patterns like [`FilesOpen::parse`], with relative paths in the given directory
+    pub fn parse_in(
+        s: &str,
+        dir: Option<&std::path::Path>,
+    ) -> Result<Vec<std::path::PathBuf>, String> {
+        let mut words = Vec::new();
+        let mut word = String::new();
+        let mut quoted = false;
+        for c in s.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                c if !quoted && (c == ',' || c.is_whitespace()) => {
+                    if !word.is_empty() {
+                        words.push(std::mem::take(&mut word));
+                    }
+                }
+                c => word.push(c),
+            }
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+        let mut pbs: Vec<std::path::PathBuf> = Vec::new();
+        for w in words {
+            let w = path::resolve(&w, dir, false).display().to_string();
+            let pattern = w.contains(['*', '?', '[']) && !std::path::Path::new(&w).is_file();
+            let found: Vec<std::path::PathBuf> = if pattern {
+                let paths = glob::glob(&w).map_err(|e| format!("{} is not a valid pattern: {}", w, e))?;
+                let found: Vec<_> = paths.filter_map(Result::ok).filter(|p| p.is_file()).collect();
+                if found.is_empty() {
+                    return Err(format!("No files match {}", w));
+                }
+                found
+            } else {
+                let p = std::path::PathBuf::from(&w);
+                if !p.exists() {
+                    return Err(format!("{} does not exist", w));
+                }
+                if !p.is_file() {
+                    return Err(format!("{} is not a file", w));
+                }
+                vec![p]
+            };
+            for p in found {
+                if !pbs.contains(&p) {
+                    pbs.push(p);
+                }
+            }
+        }
+        if pbs.is_empty() {
+            return Err("No files entered".to_string());
+        }
+        Ok(pbs)
+    }
+
+    /// The list of files in the form accepted by [`FilesOpen::parse`]
+    pub fn to_line(&self) -> String {
+        self.pbs
+            .iter()
+            .map(|p| {
+                let s = p.display().to_string();
+                if s.contains(|c: char| c == ',' || c.is_whitespace()) {
+                    format!("\"{}\"", s)
+                } else {
+                    s
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Use the dialog settings given in the options
+    pub(crate) fn apply_options(&mut self, options: &PromptOptions) {
+        apply_option(&mut self.filter, &options.filter);
+        apply_option(&mut self.initial_dir, &options.initial_dir);
+        apply_option(&mut self.title, &options.title);
+    }
+
+    /// Check that at least one file is selected and that every file exists and is a regular file
+    pub fn check_files(&self) -> Result<(), String> {
+        if self.pbs.is_empty() {
+            return Err("No files selected".to_string());
+        }
+        match self.pbs.iter().find(|p| !p.is_file()) {
+            Some(p) if !p.exists() => Err(format!("Selected file {} does not exist", p.display())),
+            Some(p) => Err(format!("Selected path {} is not a file", p.display())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<Vec<std::path::PathBuf>> for FilesOpen {
+    fn from(value: Vec<std::path::PathBuf>) -> Self {
+        Self {
+            pbs: value,
+            filter: None,
+            initial_dir: None,
+            title: None,
+        }
+    }
+}
+
+impl std::ops::Deref for FilesOpen {
+    type Target = Vec<std::path::PathBuf>;
+    fn deref(&self) -> &Self::Target {
+        &self.pbs
+    }
+}
+
+impl std::ops::DerefMut for FilesOpen {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.pbs
+    }
+}
+
+/// This is used to select an existing directory on the filesystem
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirectoryOpen {
+    /// The pathbuf
+    pb: std::path::PathBuf,
+    /// The initial directory
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub initial_dir: Option<std::path::PathBuf>,
+    /// The folder dialog title
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub title: Option<String>,
+    /// The path as it was typed on the command line, before it was expanded
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    typed: Option<String>,
+}
+
+impl DirectoryOpen {
+    /// The path as it was typed on the command line, before it was expanded, None if it was not typed
+    pub fn typed(&self) -> Option<&str> {
+        self.typed.as_deref()
+    }
+
+    /// Use the dialog settings given in the options
+    pub(crate) fn apply_options(&mut self, options: &PromptOptions) {
+        apply_option(&mut self.initial_dir, &options.initial_dir);
+        apply_option(&mut self.title, &options.title);
+    }
+}
+
+impl From<std::path::PathBuf> for DirectoryOpen {
+    fn from(value: std::path::PathBuf) -> Self {
+        Self {
+            pb: value,
+            initial_dir: None,
+            title: None,
+            typed: None,
+        }
+    }
+}
+
+impl std::ops::Deref for DirectoryOpen {
+    type Target = std::path::PathBuf;
+    fn deref(&self) -> &Self::Target {
+        &self.pb
+    }
+}
+
+impl std::ops::DerefMut for DirectoryOpen {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.pb
+    }
+}
+
+/// This is used to select a directory that may not exist yet, such as a directory for output.
+/// A directory that does not exist must have an existing parent, so that it can be created with [`DirectoryCreate::create`].
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirectoryCreate {
+    /// The pathbuf
+    pb: std::path::PathBuf,
+    /// The initial directory
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub initial_dir: Option<std::path::PathBuf>,
+    /// The folder dialog title
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub title: Option<String>,
+    /// The path as it was typed on the command line, before it was expanded
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    typed: Option<String>,
+}
+
+impl DirectoryCreate {
+    /// The path as it was typed on the command line, before it was expanded, None if it was not typed
+    pub fn typed(&self) -> Option<&str> {
+        self.typed.as_deref()
+    }
+
+    /// Use the dialog settings given in the options
+    pub(crate) fn apply_options(&mut self, options: &PromptOptions) {
+        apply_option(&mut self.initial_dir, &options.initial_dir);
+        apply_option(&mut self.title, &options.title);
+    }
+
+    /// Create the directory if it does not exist
+    pub fn create(&self) -> std::io::Result<()> {
+        if self.pb.is_dir() {
+            Ok(())
+        } else {
+            std::fs::create_dir(&self.pb)
+        }
+    }
+}
+
+impl From<std::path::PathBuf> for DirectoryCreate {
+    fn from(value: std::path::PathBuf) -> Self {
+        Self {
+            pb: value,
+            initial_dir: None,
+            title: None,
+            typed: None,
+        }
+    }
+}
+
+impl std::ops::Deref for DirectoryCreate {
+    type Target = std::path::PathBuf;
+    fn deref(&self) -> &Self::Target {
+        &self.pb
+    }
+}
+
+impl std::ops::DerefMut for DirectoryCreate {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.pb
+    }
+}
+
+/// Implements PartialEq for the types holding paths, comparing only the paths selected and not the dialog settings or checks
+macro_rules! path_eq {
+    ($($t:ty => $field:ident),*) => {
+        $(
+            impl PartialEq for $t {
+                fn eq(&self, other: &Self) -> bool {
+                    self.$field == other.$field
+                }
+            }
+        )*
+    };
+}
+
+path_eq!(
+    FileOpen => pb,
+    FileCreate => pb,
+    FilesOpen => pbs,
+    DirectoryOpen => pb,
+    DirectoryCreate => pb
+);
+
+/// Check that a path is an existing directory
+pub(crate) fn check_directory_open(pb: &std::path::Path) -> Result<(), String> {
+    if pb.is_dir() {
+        Ok(())
+    } else if pb.exists() {
+        Err(format!("Selected path {} is not a directory", pb.display()))
+    } else {
+        Err("Selected directory does not exist".to_string())
+    }
+}
+
+/// Check that a path is an existing directory, or a directory that can be created in an existing directory
+pub(crate) fn check_directory_create(pb: &std::path::Path) -> Result<(), String> {
+    if pb.is_dir() {
+        return Ok(());
+    }
+    if pb.exists() {
+        return Err(format!("Selected path {} is not a directory", pb.display()));
+    }
+    match pb.parent() {
+        Some(p) if p.as_os_str().is_empty() || p.is_dir() => Ok(()),
+        _ => Err(format!(
+            "The parent of the selected directory {} does not exist",
+            pb.display()
+        )),
+    }
+}
+
 #[cfg(feature = "egui")]
 impl EguiPrompting for FileOpen {
     fn build_gui(
@@ -96,12 +708,7 @@ impl EguiPrompting for FileOpen {
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        if let Some(comment) = comment {
-            ui.label(comment);
-        }
-        if let Some(n) = name {
-            ui.label(n);
-        }
+        form::labels(ui, name, comment);
         let mut show_file_dialog = false;
         let sel = format!("Selected file: {}", self.pb.display());
         let l = egui::Label::new(sel).sense(egui::Sense::click());
@@ -111,7 +718,7 @@ impl EguiPrompting for FileOpen {
         let n = if let Some(n) = name {
             format!(" {}", n)
         } else {
-            format!("")
+            String::new()
         };
         if ui.button(format!("Update{}", n)).clicked() {
             show_file_dialog = true;
@@ -132,38 +739,51 @@ impl EguiPrompting for FileOpen {
             }
             if let Some(path) = dialog.pick_file() {
                 self.pb = path;
+                self.typed = None;
             }
         }
+        self.revalidate();
         self.check(name)
     }
 
     fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        if self.pb.exists() {
-            Ok(())
-        } else {
-            Err("Selected file does not exist".to_string())
-        }
+        self.check_path()
     }
-}
 
-impl Prompting for FileOpen {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        let mut pb;
-        loop {
-            pb = <std::path::PathBuf as Prompting>::prompt(name, comment)?;
-            if !pb.exists() {
-                println!("That does not exist, please try again");
-            } else {
-                break;
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        self.apply_options(options);
+        self.build_gui(ui, name, comment)
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        let mut f = self.clone();
+        f.apply_options(options);
+        f.check(name)
+    }
+}
+
+impl Prompting for FileOpen {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
+        loop {
+            let (typed, pb) = options.prompt_path(name, comment)?;
+            let mut f: FileOpen = pb.into();
+            f.typed = Some(typed);
+            f.apply_options(options);
+            match f.check_path() {
+                Ok(()) => return Ok(f),
+                Err(e) => println!("{}, please try again", e),
             }
         }
-        Ok(Self {
-            pb,
-            filter: None,
-            initial_dir: None,
-            initial_file: None,
-            title: None,
-        })
     }
 }
 
@@ -175,12 +795,7 @@ impl EguiPrompting for FileCreate {
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        if let Some(comment) = comment {
-            ui.label(comment);
-        }
-        if let Some(n) = name {
-            ui.label(n);
-        }
+        form::labels(ui, name, comment);
         let mut show_file_dialog = false;
         let sel = format!("Selected file: {}", self.pb.display());
         let l = egui::Label::new(sel).sense(egui::Sense::click());
@@ -190,7 +805,7 @@ impl EguiPrompting for FileCreate {
         let n = if let Some(n) = name {
             format!(" {}", n)
         } else {
-            format!("")
+            String::new()
         };
         if ui.button(format!("Update{}", n)).clicked() {
             show_file_dialog = true;
@@ -208,395 +823,629 @@ impl EguiPrompting for FileCreate {
             }
             if let Some(path) = dialog.save_file() {
                 self.pb = path;
+                self.typed = None;
+                self.confirmed = false;
+                self.apply_extension();
             }
         }
+        if self.overwrite == Overwrite::Ask && self.pb.is_file() {
+            ui.checkbox(&mut self.confirmed, "Replace the existing file");
+        }
+        self.recheck_dir();
         self.check(name)
     }
 
     fn check(&self, _name: Option<&str>,) -> Result<(), String> {
-        if !self.pb.exists() {
-            Ok(())
-        } else {
-            Err(format!("Selected file {} already exists", self.pb.display()))
-        }
+        self.check_path(self.overwrite)
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        self.apply_options(options);
+        self.build_gui(ui, name, comment)
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        let mut f = self.clone();
+        f.apply_options(options);
+        f.check(name)
     }
 }
 
 impl Prompting for FileCreate {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        let mut pb;
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
         loop {
-            pb = <std::path::PathBuf as Prompting>::prompt(name, comment)?;
-            if pb.exists() {
-                println!("That already exists, please try again");
-            } else {
-                break;
+            let (typed, pb) = options.prompt_path(name, comment)?;
+            let mut f: FileCreate = pb.into();
+            f.typed = Some(typed);
+            f.apply_options(options);
+            f.apply_extension();
+            if f.overwrite == Overwrite::Ask && f.is_file() {
+                f.confirmed = bool::prompt(Some("That file already exists, replace it?"), None)?;
+                if !f.confirmed {
+                    continue;
+                }
+            }
+            match f.check_path(f.overwrite) {
+                Ok(()) => return Ok(f),
+                Err(e) => println!("{}, please try again", e),
             }
         }
-        Ok(Self {
-            pb,
-            filter: None,
-            initial_dir: None,
-            title: None,
-        })
     }
 }
 
 #[cfg(feature = "egui")]
-pub use userprompt_derive::EguiPrompting;
-
-#[cfg(feature = "egui")]
-pub use egui;
-
-#[cfg(feature = "egui")]
-/// The trait involved in building a input form for egui
-pub trait EguiPrompting: Sized {
+impl EguiPrompting for FilesOpen {
     fn build_gui(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
-    ) -> Result<(), String>;
+    ) -> Result<(), String> {
+        form::labels(ui, name, comment);
+        let mut show_file_dialog = false;
+        ui.label(format!("Selected files: {}", self.pbs.len()));
+        for pb in &self.pbs {
+            let l = egui::Label::new(pb.display().to_string()).sense(egui::Sense::click());
+            if ui.add(l).clicked() {
+                show_file_dialog = true;
+            }
+        }
+        let n = if let Some(n) = name {
+            format!(" {}", n)
+        } else {
+            String::new()
+        };
+        if ui.button(format!("Update{}", n)).clicked() {
+            show_file_dialog = true;
+        }
+        if show_file_dialog {
+            let mut dialog = rfd::FileDialog::new();
+            if let Some((a, b)) = &self.filter {
+                dialog = dialog.add_filter(a, b);
+            }
+            if let Some(p) = &self.initial_dir {
+                dialog = dialog.set_directory(p);
+            }
+            if let Some(t) = &self.title {
+                dialog = dialog.set_title(t);
+            }
+            if let Some(paths) = dialog.pick_files() {
+                self.pbs = paths;
+            }
+        }
+        self.check(name)
+    }
 
-    /// Perform any additional checks required in order to check the object for validness
-    fn check(&self, name: Option<&str>,) -> Result<(), String>;
-}
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        self.check_files()
+    }
 
-#[cfg(feature = "egui")]
-impl EguiPrompting for String {
-    fn build_gui(
+    fn build_gui_with(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
+        options: &PromptOptions,
     ) -> Result<(), String> {
-        if let Some(comment) = comment {
-            ui.label(comment);
-        }
-        if let Some(n) = name {
-            ui.label(n);
-        }
-        ui.text_edit_singleline(self);
-        Ok(())
+        self.apply_options(options);
+        self.build_gui(ui, name, comment)
+    }
+}
+
+impl Prompting for FilesOpen {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
     }
 
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
+        options.print_dialog();
+        let dir = options.initial_dir.as_deref();
+        let mut f: FilesOpen = crate::prompt_parsed(name, comment, |s| {
+            FilesOpen::parse_in(s, dir).map(|pbs| pbs.into())
+        })?;
+        f.apply_options(options);
+        Ok(f)
     }
 }
 
+/// Show the selected directory and a button that opens a folder dialog to change it, returns true when it was changed
 #[cfg(feature = "egui")]
-impl EguiPrompting for Password {
+fn directory_edit(
+    ui: &mut egui::Ui,
+    pb: &mut std::path::PathBuf,
+    initial_dir: &Option<std::path::PathBuf>,
+    title: &Option<String>,
+    name: Option<&str>,
+    comment: Option<&str>,
+) -> bool {
+    form::labels(ui, name, comment);
+    let mut show_dialog = false;
+    let sel = format!("Selected directory: {}", pb.display());
+    let l = egui::Label::new(sel).sense(egui::Sense::click());
+    if ui.add(l).clicked() {
+        show_dialog = true;
+    }
+    let n = if let Some(n) = name {
+        format!(" {}", n)
+    } else {
+        String::new()
+    };
+    if ui.button(format!("Update{}", n)).clicked() {
+        show_dialog = true;
+    }
+    if show_dialog {
+        let mut dialog = rfd::FileDialog::new();
+        if let Some(p) = initial_dir {
+            dialog = dialog.set_directory(p);
+        }
+        if let Some(t) = title {
+            dialog = dialog.set_title(t);
+        }
+        if let Some(path) = dialog.pick_folder() {
+            *pb = path;
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(feature = "egui")]
+impl EguiPrompting for DirectoryOpen {
     fn build_gui(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        if let Some(comment) = comment {
-            ui.label(comment);
-        }
-        if let Some(n) = name {
-            ui.label(n);
+        if directory_edit(ui, &mut self.pb, &self.initial_dir, &self.title, name, comment) {
+            self.typed = None;
         }
-        let p: &mut String = &mut self.0;
-        let pe = egui::TextEdit::singleline(p).password(true);
-        ui.add(pe);
         self.check(name)
     }
 
-    fn check(&self, name: Option<&str>) -> Result<(), String> {
-        if self.0.is_empty() {
-            return Err(format!("{} password is blank", name.unwrap_or("")));
-        }
-        Ok(())
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        check_directory_open(&self.pb)
     }
-}
 
-#[cfg(feature = "egui")]
-impl EguiPrompting for Password2 {
-    fn build_gui(
+    fn build_gui_with(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
+        options: &PromptOptions,
     ) -> Result<(), String> {
-        if let Some(comment) = comment {
-            ui.label(comment);
-        }
-        if let Some(n) = name {
-            ui.label(n);
-        }
-        let p: &mut String = &mut self.0;
-        let pe = egui::TextEdit::singleline(p).password(true);
-        ui.add(pe);
-        let p: &mut String = &mut self.1;
-        let pe = egui::TextEdit::singleline(p).password(true);
-        ui.add(pe);
-        self.check(name)
+        self.apply_options(options);
+        self.build_gui(ui, name, comment)
     }
+}
 
-    fn check(&self, name: Option<&str>) -> Result<(), String> {
-        if !self.0.is_empty() && self.0 == self.1 {
-            Ok(())
-        } else {
-            Err(format!("{} password does not match", name.unwrap_or("")))
+impl Prompting for DirectoryOpen {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
+        loop {
+            let (typed, pb) = options.prompt_path(name, comment)?;
+            match check_directory_open(&pb) {
+                Ok(()) => {
+                    let mut d: DirectoryOpen = pb.into();
+                    d.typed = Some(typed);
+                    d.apply_options(options);
+                    return Ok(d);
+                }
+                Err(e) => println!("{}, please try again", e),
+            }
         }
     }
 }
 
+/// A directory that does not exist yet has a button to create it.
 #[cfg(feature = "egui")]
-impl EguiPrompting for u8 {
+impl EguiPrompting for DirectoryCreate {
     fn build_gui(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
+        if directory_edit(ui, &mut self.pb, &self.initial_dir, &self.title, name, comment) {
+            self.typed = None;
         }
-        Ok(())
+        let r = self.check(name);
+        if r.is_ok() && !self.pb.is_dir() && ui.button("Create directory").clicked() {
+            self.create()
+                .map_err(|e| format!("Failed to create {}: {}", self.pb.display(), e))?;
+        }
+        r
     }
 
     fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
+        check_directory_create(&self.pb)
     }
-}
 
-#[cfg(feature = "egui")]
-impl EguiPrompting for i8 {
-    fn build_gui(
+    fn build_gui_with(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
+        options: &PromptOptions,
     ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
+        self.apply_options(options);
+        self.build_gui(ui, name, comment)
     }
+}
 
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
+/// A directory that does not exist is created once the user confirms it.
+impl Prompting for DirectoryCreate {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
+        loop {
+            let (typed, pb) = options.prompt_path(name, comment)?;
+            if let Err(e) = check_directory_create(&pb) {
+                println!("{}, please try again", e);
+                continue;
+            }
+            let mut d: DirectoryCreate = pb.into();
+            d.typed = Some(typed);
+            d.apply_options(options);
+            if d.is_dir() {
+                return Ok(d);
+            }
+            if bool::prompt(Some("That directory does not exist, create it?"), None)? {
+                match d.create() {
+                    Ok(()) => return Ok(d),
+                    Err(e) => println!("Failed to create {}: {}, please try again", d.display(), e),
+                }
+            }
+        }
     }
 }
 
 #[cfg(feature = "egui")]
-impl EguiPrompting for u16 {
+pub use userprompt_derive::EguiPrompting;
+
+#[cfg(feature = "egui")]
+pub mod egui;
+
+#[cfg(feature = "egui")]
+/// The trait involved in building a input form for egui
+pub trait EguiPrompting: Sized {
     fn build_gui(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
-    ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
-    }
+    ) -> Result<(), String>;
 
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
-    }
-}
+    /// Perform any additional checks required in order to check the object for validness
+    fn check(&self, name: Option<&str>,) -> Result<(), String>;
 
-#[cfg(feature = "egui")]
-impl EguiPrompting for i16 {
-    fn build_gui(
+    /// Build the input form, using options usually given by the prompt attribute of the derive macro.
+    /// Types that have no options build the same form as build_gui.
+    fn build_gui_with(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
+        _options: &PromptOptions,
     ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
+        self.build_gui(ui, name, comment)
     }
-    
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
+
+    /// Check the object, using options usually given by the prompt attribute of the derive macro.
+    fn check_with(&self, name: Option<&str>, _options: &PromptOptions) -> Result<(), String> {
+        self.check(name)
+    }
+
+    /// Returns true if the form for the object is made of other fields, such as a struct or a list,
+    /// so that a layout can show it in a section of its own.
+    fn is_nested_form(&self) -> bool {
+        false
     }
 }
 
 #[cfg(feature = "egui")]
-impl EguiPrompting for u32 {
+impl EguiPrompting for String {
     fn build_gui(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
+        form::labels(ui, name, comment);
+        ui.text_edit_singleline(self);
         Ok(())
     }
-    
+
     fn check(&self, _name: Option<&str>) -> Result<(), String> {
         Ok(())
     }
 }
 
+/// Show a text edit for a value that is parsed from a string.
+/// The text is kept in the egui memory while it is being edited or does not parse, and the reason it does not parse is shown under it.
+#[cfg(feature = "egui")]
+pub(crate) fn parsed_edit<T: std::fmt::Display>(
+    ui: &mut egui::Ui,
+    value: &mut T,
+    name: Option<&str>,
+    comment: Option<&str>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<(), String> {
+    form::labels(ui, name, comment);
+    let id = ui.make_persistent_id(("parsed", name));
+    let mut text = ui
+        .data(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| value.to_string());
+    let response = ui.text_edit_singleline(&mut text);
+    match parse(&text) {
+        Ok(v) => {
+            *value = v;
+            if response.has_focus() {
+                ui.data_mut(|d| d.insert_temp(id, text));
+            } else {
+                ui.data_mut(|d| d.remove::<String>(id));
+            }
+            Ok(())
+        }
+        Err(e) => {
+            form::report_error(ui, name.unwrap_or(""), &e);
+            ui.data_mut(|d| d.insert_temp(id, text));
+            Err(format!("{} {}", name.unwrap_or(""), e))
+        }
+    }
+}
+
+/// Show a text edit for a password, with a button to show the password when reveal is true.
+/// Returns the response of the text edit.
+#[cfg(feature = "egui")]
+fn password_edit(ui: &mut egui::Ui, p: &mut String, id: egui::Id, reveal: bool) -> egui::Response {
+    if !reveal {
+        return ui.add(egui::TextEdit::singleline(p).password(true));
+    }
+    let mut shown = ui.data(|d| d.get_temp::<bool>(id).unwrap_or(false));
+    let response = ui.horizontal(|ui| {
+        let response = ui.add(egui::TextEdit::singleline(p).password(!shown));
+        if ui
+            .selectable_label(shown, "👁")
+            .on_hover_text(if shown { "Hide password" } else { "Show password" })
+            .clicked()
+        {
+            shown = !shown;
+        }
+        response
+    });
+    ui.data_mut(|d| d.insert_temp(id, shown));
+    response.inner
+}
+
 #[cfg(feature = "egui")]
-impl EguiPrompting for i32 {
+impl EguiPrompting for Password {
     fn build_gui(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
-    }
-    
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
     }
-}
 
-#[cfg(feature = "egui")]
-impl EguiPrompting for u64 {
-    fn build_gui(
+    fn build_gui_with(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
+        options: &PromptOptions,
     ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
+        form::labels(ui, name, comment);
+        let id = ui.make_persistent_id(("reveal", name));
+        password_edit(ui, &mut self.0, id, options.reveal);
+        self.check(name)
     }
-    
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        if self.0.is_empty() {
+            return Err(format!("{} password is blank", name.unwrap_or("")));
+        }
         Ok(())
     }
 }
 
 #[cfg(feature = "egui")]
-impl EguiPrompting for i64 {
+impl EguiPrompting for Password2 {
     fn build_gui(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
     }
-    
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        if !self.0.is_empty() && self.0 == self.1 {
+            Ok(())
+        } else {
+            Err(format!("{} password does not match", name.unwrap_or("")))
+        }
     }
-}
 
-#[cfg(feature = "egui")]
-impl EguiPrompting for usize {
-    fn build_gui(
+    fn build_gui_with(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
+        options: &PromptOptions,
     ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
+        self.edit_gui(ui, name, comment, options).0
     }
-    
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
+
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        self.check(name)?;
+        self.check_options(name, options)
     }
 }
 
 #[cfg(feature = "egui")]
-impl EguiPrompting for isize {
-    fn build_gui(
+impl Password2 {
+    /// Show the form for the password, returning the result of checking it and the responses of both text edits
+    pub(crate) fn edit_gui(
         &mut self,
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
-    ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
+        options: &PromptOptions,
+    ) -> (Result<(), String>, [egui::Response; 2]) {
+        form::labels(ui, name, comment);
+        let id = ui.make_persistent_id(("reveal", name));
+        let first = password_edit(ui, &mut self.0, id, options.reveal);
+        let second = password_edit(ui, &mut self.1, id.with(1), options.reveal);
+        password::strength_meter(ui, &self.0);
+        let r = self.check(name);
+        if let Some(p) = options.password_policy.as_ref().filter(|_| !self.0.is_empty()) {
+            if let Err(reasons) = self.check_policy(p) {
+                if let Err(e) = &r {
+                    form::report_error(ui, name.unwrap_or(""), e);
+                }
+                for reason in reasons {
+                    form::report_error(ui, name.unwrap_or(""), &format!("The password {}", reason));
+                }
+            }
         }
-        Ok(())
-    }
-    
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
+        (r.and_then(|_| self.check_with(name, options)), [first, second])
     }
 }
 
+/// Implements EguiPrompting for numbers.
+/// With a minimum and maximum in the options the number is shown as a slider, otherwise as a clamped drag value when any options are given.
+/// The 128 bit integers are not supported by the egui widgets, and are entered as text instead.
 #[cfg(feature = "egui")]
-impl EguiPrompting for f32 {
-    fn build_gui(
-        &mut self,
-        ui: &mut egui::Ui,
-        name: Option<&str>,
-        comment: Option<&str>,
-    ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
-    }
-    
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
-    }
+macro_rules! egui_number {
+    ($($t:ty),*) => {
+        $(
+            impl EguiPrompting for $t {
+                fn build_gui(
+                    &mut self,
+                    ui: &mut egui::Ui,
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                ) -> Result<(), String> {
+                    let mut s = self.to_string();
+                    s.build_gui(ui, name, comment)?;
+                    if let Ok(val) = s.parse::<Self>() {
+                        *self = val;
+                    }
+                    Ok(())
+                }
+
+                fn check(&self, _name: Option<&str>) -> Result<(), String> {
+                    Ok(())
+                }
+
+                fn build_gui_with(
+                    &mut self,
+                    ui: &mut egui::Ui,
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                    options: &PromptOptions,
+                ) -> Result<(), String> {
+                    use egui::emath::Numeric;
+                    if options.min.is_none() && options.max.is_none() && options.step.is_none() && options.unit.is_none() {
+                        return self.build_gui(ui, name, comment);
+                    }
+                    form::labels(ui, name, comment);
+                    let unit = options.unit.as_ref().map(|u| format!(" {}", u)).unwrap_or_default();
+                    if let (Some(min), Some(max)) = (options.min, options.max) {
+                        let mut s = egui::Slider::new(self, <$t>::from_f64(min)..=<$t>::from_f64(max)).suffix(unit);
+                        if let Some(step) = options.step {
+                            s = s.step_by(step);
+                        }
+                        ui.add(s);
+                    } else {
+                        let min = options.min.unwrap_or(<$t>::MIN.to_f64());
+                        let max = options.max.unwrap_or(<$t>::MAX.to_f64());
+                        let mut d = egui::DragValue::new(self).clamp_range(min..=max).suffix(unit);
+                        if let Some(step) = options.step {
+                            d = d.speed(step);
+                        }
+                        ui.add(d);
+                    }
+                    self.check_with(name, options)
+                }
+
+                fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+                    options.check_range(*self as f64).map_err(|e| format!("{} {}", name.unwrap_or(""), e))
+                }
+            }
+        )*
+    };
 }
 
 #[cfg(feature = "egui")]
-impl EguiPrompting for f64 {
-    fn build_gui(
-        &mut self,
-        ui: &mut egui::Ui,
-        name: Option<&str>,
-        comment: Option<&str>,
-    ) -> Result<(), String> {
-        let mut s = self.to_string();
-        s.build_gui(ui, name, comment)?;
-        if let Ok(val) = s.parse::<Self>() {
-            *self = val;
-        }
-        Ok(())
-    }
-    
-    fn check(&self, _name: Option<&str>) -> Result<(), String> {
-        Ok(())
-    }
+egui_number!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, f32, f64);
+
+/// Implements EguiPrompting for types that are parsed from a string.
+/// The value is only changed when the text entered is valid.
+#[cfg(feature = "egui")]
+macro_rules! egui_parsed {
+    ($($t:ty),*) => {
+        $(
+            impl EguiPrompting for $t {
+                fn build_gui(
+                    &mut self,
+                    ui: &mut egui::Ui,
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                ) -> Result<(), String> {
+                    let mut s = self.to_string();
+                    s.build_gui(ui, name, comment)?;
+                    if let Ok(val) = s.parse::<Self>() {
+                        *self = val;
+                    }
+                    Ok(())
+                }
+
+                fn check(&self, _name: Option<&str>) -> Result<(), String> {
+                    Ok(())
+                }
+            }
+        )*
+    };
 }
 
+#[cfg(feature = "egui")]
+egui_parsed!(
+    u128,
+    i128,
+    char,
+    std::num::NonZeroU8,
+    std::num::NonZeroI8,
+    std::num::NonZeroU16,
+    std::num::NonZeroI16,
+    std::num::NonZeroU32,
+    std::num::NonZeroI32,
+    std::num::NonZeroU64,
+    std::num::NonZeroI64,
+    std::num::NonZeroU128,
+    std::num::NonZeroI128,
+    std::num::NonZeroUsize,
+    std::num::NonZeroIsize
+);
+
 #[cfg(feature = "egui")]
 impl EguiPrompting for bool {
     fn build_gui(
@@ -605,10 +1454,9 @@ impl EguiPrompting for bool {
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        if let Some(comment) = comment {
-            ui.label(comment);
-        }
-        let cname = if let Some(n) = name {
+        let cname = if !form::labels(ui, None, comment) {
+            String::new()
+        } else if let Some(n) = name {
             n.to_string()
         } else {
             "Item".to_string()
@@ -641,15 +1489,51 @@ impl EguiPrompting for std::path::PathBuf {
     }
 }
 
-/// A hashmap with a selection added
+/// A hashmap with a selection added.
+/// The keys are shown in sorted order, and must be unique and not empty.
+/// The selection is only serialized when [`SelectedHashMap::set_persist_selection`] is used,
+/// or when the map was deserialized with a selection.
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "SelectedHashMapData<T>"))]
 pub struct SelectedHashMap<T> {
     map: std::collections::HashMap<String, T>,
-    #[cfg_attr(feature = "serde", serde(skip))]
     selection: Option<String>,
-    #[cfg_attr(feature = "serde", serde(skip))]
-    new_selection: String,
+    persist_selection: bool,
+}
+
+/// The serialized form of a SelectedHashMap
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SelectedHashMapData<T> {
+    map: std::collections::HashMap<String, T>,
+    #[serde(default)]
+    selection: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> From<SelectedHashMapData<T>> for SelectedHashMap<T> {
+    fn from(value: SelectedHashMapData<T>) -> Self {
+        Self {
+            persist_selection: value.selection.is_some(),
+            map: value.map,
+            selection: value.selection,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for SelectedHashMap<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let persist = self.persist_selection && self.selected().is_some();
+        let mut s = serializer.serialize_struct("SelectedHashMap", 1 + persist as usize)?;
+        s.serialize_field("map", &self.map)?;
+        if persist {
+            s.serialize_field("selection", &self.selection)?;
+        }
+        s.end()
+    }
 }
 
 impl<T> SelectedHashMap<T> {
@@ -658,7 +1542,7 @@ impl<T> SelectedHashMap<T> {
         Self {
             map: std::collections::HashMap::new(),
             selection: None,
-            new_selection: String::new(),
+            persist_selection: false,
         }
     }
 
@@ -671,6 +1555,114 @@ impl<T> SelectedHashMap<T> {
     pub fn map_mut(&mut self) -> &mut std::collections::HashMap<String, T> {
         &mut self.map
     }
+
+    /// Get the keys of the map in sorted order
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.map.keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        keys
+    }
+
+    /// Get the key of the selected entry
+    pub fn selected(&self) -> Option<&str> {
+        self.selection
+            .as_deref()
+            .filter(|s| self.map.contains_key(*s))
+    }
+
+    /// Get the value of the selected entry
+    pub fn selected_value(&self) -> Option<&T> {
+        self.selection.as_ref().and_then(|s| self.map.get(s))
+    }
+
+    /// Get a mutable reference to the value of the selected entry
+    pub fn selected_value_mut(&mut self) -> Option<&mut T> {
+        match &self.selection {
+            Some(s) => self.map.get_mut(s),
+            None => None,
+        }
+    }
+
+    /// Select an entry, returns false and leaves the selection unchanged if there is no entry with the key
+    pub fn select(&mut self, key: &str) -> bool {
+        if self.map.contains_key(key) {
+            self.selection = Some(key.to_string());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Clear the selection
+    pub fn deselect(&mut self) {
+        self.selection = None;
+    }
+
+    /// Serialize the selection along with the map, it is not serialized by default
+    pub fn set_persist_selection(&mut self, persist: bool) {
+        self.persist_selection = persist;
+    }
+
+    /// Check that a key can be used for a new entry
+    pub fn check_key(&self, key: &str) -> Result<(), String> {
+        if key.is_empty() {
+            Err("The name of an entry cannot be empty".to_string())
+        } else if self.map.contains_key(key) {
+            Err(format!("An entry named {} already exists", key))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Add a new entry and select it
+    pub fn insert(&mut self, key: String, value: T) -> Result<(), String> {
+        self.check_key(&key)?;
+        self.map.insert(key.clone(), value);
+        self.selection = Some(key);
+        Ok(())
+    }
+
+    /// Give an entry a new key, the selection follows the entry
+    pub fn rename(&mut self, key: &str, new_key: String) -> Result<(), String> {
+        self.check_key(&new_key)?;
+        let v = self
+            .map
+            .remove(key)
+            .ok_or_else(|| format!("There is no entry named {}", key))?;
+        if self.selection.as_deref() == Some(key) {
+            self.selection = Some(new_key.clone());
+        }
+        self.map.insert(new_key, v);
+        Ok(())
+    }
+
+    /// Remove an entry, clearing the selection if it was selected
+    pub fn remove(&mut self, key: &str) -> Option<T> {
+        if self.selection.as_deref() == Some(key) {
+            self.selection = None;
+        }
+        self.map.remove(key)
+    }
+
+    /// Add a copy of an entry with a new key and select it
+    pub fn clone_entry(&mut self, key: &str, new_key: String) -> Result<(), String>
+    where
+        T: Clone,
+    {
+        let v = self
+            .map
+            .get(key)
+            .cloned()
+            .ok_or_else(|| format!("There is no entry named {}", key))?;
+        self.insert(new_key, v)
+    }
+}
+
+/// Maps are equal when they have the same entries and selection, whether the selection is persisted is not compared
+impl<T: PartialEq> PartialEq for SelectedHashMap<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map && self.selection == other.selection
+    }
 }
 
 impl<T> Default for SelectedHashMap<T> {
@@ -679,6 +1671,115 @@ impl<T> Default for SelectedHashMap<T> {
     }
 }
 
+/// Show a map with a selection, with a button to copy the selected entry when a copy function is given.
+/// The names typed for new and renamed entries are kept in the egui memory.
+#[cfg(feature = "egui")]
+fn build_selected_map_gui<T: EguiPrompting + Default>(
+    map: &mut SelectedHashMap<T>,
+    ui: &mut egui::Ui,
+    name: Option<&str>,
+    comment: Option<&str>,
+    copy: Option<fn(&T) -> T>,
+) -> Result<(), String> {
+    form::labels(ui, name, comment);
+    let n = if let Some(n) = name {
+        format!("{} ", n)
+    } else {
+        " ".to_string()
+    };
+    let id = ui.make_persistent_id(("selected map", name));
+    let error_color = ui.visuals().error_fg_color;
+    let selected = map.selected().map(|s| s.to_string());
+
+    let nid = id.with("new");
+    let mut new_name: String = ui.data(|d| d.get_temp(nid)).unwrap_or_default();
+    ui.label(format!("Name for new {}", n));
+    ui.text_edit_singleline(&mut new_name);
+    let new_error = map.check_key(&new_name).err();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(new_error.is_none(), egui::Button::new("Add new entry"))
+            .clicked()
+            && map.insert(new_name.clone(), T::default()).is_ok()
+        {
+            new_name.clear();
+        }
+        if let (Some(s), Some(copy)) = (&selected, copy) {
+            if ui
+                .add_enabled(new_error.is_none(), egui::Button::new("Copy selected entry"))
+                .clicked()
+            {
+                let v = copy(&map.map[s]);
+                if map.insert(new_name.clone(), v).is_ok() {
+                    new_name.clear();
+                }
+            }
+        }
+    });
+    if let Some(e) = new_error.filter(|_| !new_name.is_empty()) {
+        ui.colored_label(error_color, e);
+    }
+    ui.data_mut(|d| d.insert_temp(nid, new_name));
+
+    let keys: Vec<String> = map.keys().iter().map(|k| k.to_string()).collect();
+    egui::ComboBox::from_label(format!("Select a {}entry!", n))
+        .selected_text(selected.as_deref().unwrap_or("Selection"))
+        .show_ui(ui, |ui| {
+            for elem in &keys {
+                if ui
+                    .selectable_label(selected.as_ref() == Some(elem), elem)
+                    .clicked()
+                {
+                    map.select(elem);
+                }
+            }
+        });
+    if let Some(s) = map.selected().map(|s| s.to_string()) {
+        ui.label(format!("Entry {}", s));
+        let rid = id.with(("rename", &s));
+        let mut rename: String = ui.data(|d| d.get_temp(rid)).unwrap_or_else(|| s.clone());
+        let rename_error = if rename == s {
+            None
+        } else {
+            map.check_key(&rename).err()
+        };
+        let mut renamed = false;
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut rename);
+            if ui
+                .add_enabled(rename != s && rename_error.is_none(), egui::Button::new("Rename"))
+                .clicked()
+            {
+                renamed = map.rename(&s, rename.clone()).is_ok();
+            }
+        });
+        if let Some(e) = rename_error {
+            ui.colored_label(error_color, e);
+        }
+        ui.data_mut(|d| {
+            if renamed || rename == s {
+                d.remove::<String>(rid);
+            } else {
+                d.insert_temp(rid, rename);
+            }
+        });
+        if ui.button("Delete this entry").clicked() {
+            map.remove(&s);
+        } else if !renamed {
+            let tname = if let Some(n) = name {
+                n.to_string()
+            } else {
+                "item".to_string()
+            };
+            let cname = format!("{}.[{}]", tname, s);
+            if let Some(e) = map.map.get_mut(&s) {
+                let _ = form::field(ui, &cname, |ui| e.build_gui(ui, Some(&cname), None));
+            }
+        }
+    }
+    map.check(name)
+}
+
 #[cfg(feature = "egui")]
 impl<T> EguiPrompting for SelectedHashMap<T>
 where
@@ -690,69 +1791,329 @@ where
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        if let Some(comment) = comment {
-            ui.label(comment);
+        build_selected_map_gui(self, ui, name, comment, None)
+    }
+
+    fn check(&self, name: Option<&str>,) -> Result<(), String> {
+        for a in &self.map {
+            a.1.check(name)?;
         }
-        if let Some(n) = name {
-            ui.label(n);
+        Ok(())
+    }
+
+    fn is_nested_form(&self) -> bool {
+        true
+    }
+}
+
+/// Entries that can be cloned can be copied with the button to copy the selected entry.
+#[cfg(feature = "egui")]
+impl<T> EguiDuplicate for SelectedHashMap<T>
+where
+    T: EguiPrompting + std::default::Default + Clone,
+{
+    fn build_gui_duplicate(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        _options: &PromptOptions,
+    ) -> Result<(), String> {
+        build_selected_map_gui(self, ui, name, comment, Some(T::clone))
+    }
+}
+
+/// The maps with keys that are entered as text, edited the same way on the command line and in egui
+trait KeyedMap {
+    /// The type of the keys
+    type Key: std::str::FromStr + std::fmt::Display + Clone;
+    /// The type of the values
+    type Value;
+
+    /// Returns true if the key is in the map
+    fn has_key(&self, k: &Self::Key) -> bool;
+
+    /// Insert an entry into the map
+    fn put(&mut self, k: Self::Key, v: Self::Value);
+
+    /// Remove an entry from the map
+    #[cfg(feature = "egui")]
+    fn take(&mut self, k: &Self::Key) -> Option<Self::Value>;
+
+    /// The entries of the map, in the order they are shown
+    #[cfg(feature = "egui")]
+    fn rows(&mut self) -> Vec<(&Self::Key, &mut Self::Value)>;
+
+    /// The entries of the map
+    #[cfg(feature = "egui")]
+    fn values(&self) -> Vec<(&Self::Key, &Self::Value)>;
+}
+
+impl<K, V> KeyedMap for std::collections::HashMap<K, V>
+where
+    K: std::str::FromStr + std::fmt::Display + Clone + Eq + std::hash::Hash,
+{
+    type Key = K;
+    type Value = V;
+
+    fn has_key(&self, k: &K) -> bool {
+        self.contains_key(k)
+    }
+
+    fn put(&mut self, k: K, v: V) {
+        self.insert(k, v);
+    }
+
+    #[cfg(feature = "egui")]
+    fn take(&mut self, k: &K) -> Option<V> {
+        self.remove(k)
+    }
+
+    #[cfg(feature = "egui")]
+    fn rows(&mut self) -> Vec<(&K, &mut V)> {
+        let mut rows: Vec<(&K, &mut V)> = self.iter_mut().collect();
+        rows.sort_by_cached_key(|(k, _)| k.to_string());
+        rows
+    }
+
+    #[cfg(feature = "egui")]
+    fn values(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<K, V> KeyedMap for std::collections::BTreeMap<K, V>
+where
+    K: std::str::FromStr + std::fmt::Display + Clone + Ord,
+{
+    type Key = K;
+    type Value = V;
+
+    fn has_key(&self, k: &K) -> bool {
+        self.contains_key(k)
+    }
+
+    fn put(&mut self, k: K, v: V) {
+        self.insert(k, v);
+    }
+
+    #[cfg(feature = "egui")]
+    fn take(&mut self, k: &K) -> Option<V> {
+        self.remove(k)
+    }
+
+    #[cfg(feature = "egui")]
+    fn rows(&mut self) -> Vec<(&K, &mut V)> {
+        self.iter_mut().collect()
+    }
+
+    #[cfg(feature = "egui")]
+    fn values(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+/// Check a key typed for a map, where current is the key of the entry being renamed, if any
+#[cfg(feature = "egui")]
+fn map_key_error<K>(text: &str, current: Option<&str>, used: &[String]) -> Option<String>
+where
+    K: std::str::FromStr + std::fmt::Display,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+{
+    match text.parse::<K>() {
+        Err(e) => Some(format!("Invalid key: {}", e)),
+        Ok(k) => {
+            let k = k.to_string();
+            if Some(k.as_str()) != current && used.contains(&k) {
+                Some(format!("The key {} is already used", k))
+            } else {
+                None
+            }
         }
-        let n = if let Some(n) = name {
-            format!("{} ", n)
-        } else {
-            " ".to_string()
-        };
-        ui.label(format!("Name for new {}", n));
-        ui.text_edit_singleline(&mut self.new_selection);
-        if ui.button("Add new entry").clicked() {
-            self.map.insert(self.new_selection.to_owned(), T::default());
-            self.selection = Some(self.new_selection.to_owned());
-        }
-        egui::ComboBox::from_label(format!("Select a {}entry!", n))
-            .selected_text(self.selection.as_ref().unwrap_or(&format!("Selection")))
-            .show_ui(ui, |ui| {
-                for elem in self.map.keys() {
-                    if ui.selectable_label(false, elem).clicked() {
-                        self.selection = Some(elem.to_string());
+    }
+}
+
+/// A change to the entries of a map requested in its egui form
+#[cfg(feature = "egui")]
+enum MapAction<K> {
+    /// Give an entry a new key
+    Rename(K, K),
+    /// Remove an entry
+    Remove(K),
+}
+
+/// Show a table with a row for each entry of a map, where the key can be changed and the entry removed,
+/// followed by a row to add an entry. The key being typed is kept in the egui memory until it is accepted.
+/// Returns the first error of the values.
+#[cfg(feature = "egui")]
+fn map_edit<M>(
+    map: &mut M,
+    ui: &mut egui::Ui,
+    name: Option<&str>,
+    comment: Option<&str>,
+    options: &PromptOptions,
+) -> Result<(), String>
+where
+    M: KeyedMap,
+    M::Value: EguiPrompting + Default,
+    <M::Key as std::str::FromStr>::Err: std::fmt::Display,
+{
+    form::labels(ui, name, comment);
+    let id = ui.make_persistent_id(("map", name));
+    let error_color = ui.visuals().error_fg_color;
+    let mut rows = map.rows();
+    let used: Vec<String> = rows.iter().map(|(k, _)| k.to_string()).collect();
+    let mut action = None;
+    let mut result = Ok(());
+    egui::Grid::new(id).striped(true).num_columns(3).show(ui, |ui| {
+        for (k, v) in rows.iter_mut() {
+            let ks = k.to_string();
+            let kid = id.with(("key", &ks));
+            let mut text: String = ui.data(|d| d.get_temp(kid)).unwrap_or_else(|| ks.clone());
+            ui.vertical(|ui| {
+                let r = ui.text_edit_singleline(&mut text);
+                let error = if text != ks {
+                    map_key_error::<M::Key>(&text, Some(&ks), &used)
+                } else {
+                    None
+                };
+                if let Some(e) = &error {
+                    ui.colored_label(error_color, e);
+                } else if r.lost_focus() && text != ks {
+                    if let Ok(new) = text.parse() {
+                        action = Some(MapAction::Rename((*k).clone(), new));
                     }
                 }
             });
-        if let Some(s) = &self.selection {
-            let cname = if let Some(e) = self.map.get_mut(s) {
-                let tname = if let Some(n) = name {
-                    n.to_string()
+            ui.data_mut(|d| {
+                if text == ks {
+                    d.remove::<String>(kid);
                 } else {
-                    "item".to_string()
-                };
-                ui.label(format!("Entry {}", s));
-                let cname = format!("{}.[{}]", tname, s);
-                Some((e, cname))
-            } else {
-                None
-            };
-            if ui.button("Delete this entry").clicked() {
-                self.map.remove(s);
-                self.selection = None;
-            } else {
-                if let Some((e, cname)) = cname {
-                    let _ = e.build_gui(ui, Some(&cname), None);
+                    d.insert_temp(kid, text);
                 }
+            });
+            let cname = format!("{}.[{}]", name.unwrap_or("item"), ks);
+            ui.vertical(|ui| {
+                ui.push_id(&ks, |ui| {
+                    let r = form::field(ui, &cname, |ui| {
+                        v.build_gui_with(ui, Some(&cname), None, options)
+                            .and_then(|_| v.check_with(Some(&cname), options))
+                    });
+                    if result.is_ok() {
+                        result = r;
+                    }
+                });
+            });
+            if ui.button("Delete").clicked() {
+                action = Some(MapAction::Remove((*k).clone()));
+            }
+            ui.end_row();
+        }
+    });
+    drop(rows);
+    match action {
+        Some(MapAction::Rename(old, new)) => {
+            if let Some(v) = map.take(&old) {
+                map.put(new, v);
+            }
+            ui.data_mut(|d| d.remove::<String>(id.with(("key", old.to_string()))));
+        }
+        Some(MapAction::Remove(old)) => {
+            map.take(&old);
+            ui.data_mut(|d| d.remove::<String>(id.with(("key", old.to_string()))));
+        }
+        None => {}
+    }
+    let nid = id.with("new");
+    let mut text: String = ui.data(|d| d.get_temp(nid)).unwrap_or_default();
+    let error = if text.is_empty() {
+        None
+    } else {
+        map_key_error::<M::Key>(&text, None, &used)
+    };
+    ui.horizontal(|ui| {
+        ui.label("New key");
+        ui.text_edit_singleline(&mut text);
+        if ui
+            .add_enabled(error.is_none(), egui::Button::new("Add entry"))
+            .clicked()
+        {
+            if let Ok(k) = text.parse() {
+                map.put(k, M::Value::default());
+                text.clear();
             }
         }
-        self.check(name)
+    });
+    if let Some(e) = error {
+        ui.colored_label(error_color, e);
     }
+    ui.data_mut(|d| d.insert_temp(nid, text));
+    result
+}
 
-    fn check(&self, name: Option<&str>,) -> Result<(), String> {
-        for a in &self.map {
-            a.1.check(name)?;
+/// Check every value of a map
+#[cfg(feature = "egui")]
+fn map_check<M>(map: &M, name: Option<&str>, options: &PromptOptions) -> Result<(), String>
+where
+    M: KeyedMap,
+    M::Value: EguiPrompting,
+{
+    for (k, v) in map.values() {
+        v.check_with(Some(&format!("{}.[{}]", name.unwrap_or("item"), k)), options)?;
+    }
+    Ok(())
+}
+
+/// Prompt for the entries of a map on the command line until a blank key is entered,
+/// asking again for a key that cannot be parsed or is already used
+fn prompt_map<M>(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<M, Error>
+where
+    M: KeyedMap + Default,
+    M::Value: Prompting,
+    <M::Key as std::str::FromStr>::Err: std::fmt::Display,
+{
+    use std::io::Write;
+    if let Some(comment) = comment {
+        println!("{}", comment);
+    }
+    if let Some(n) = name {
+        print!("{}: ", n);
+        std::io::stdout().flush().unwrap();
+    }
+    let mut hm = M::default();
+    loop {
+        print!("Enter key name (blank to end):");
+        std::io::stdout().flush().unwrap();
+        let key = String::prompt(None, None)?;
+        if key.is_empty() {
+            println!("Done");
+            break;
         }
-        Ok(())
+        let key: M::Key = match key.parse() {
+            Ok(k) => k,
+            Err(e) => {
+                println!("Invalid key: {}", e);
+                continue;
+            }
+        };
+        if hm.has_key(&key) {
+            println!("The key {} is already used", key);
+            continue;
+        }
+        let t = M::Value::prompt_with(None, None, options)?;
+        hm.put(key, t);
     }
+    Ok(hm)
 }
 
+/// Each entry is a row of a table, where the key can be changed and the entry deleted.
+/// A key that cannot be parsed or is already used is shown as an error next to it.
 #[cfg(feature = "egui")]
-impl<T> EguiPrompting for Vec<T>
+impl<K, V> EguiPrompting for std::collections::HashMap<K, V>
 where
-    T: EguiPrompting + std::default::Default,
+    K: std::str::FromStr + std::fmt::Display + Clone + Eq + std::hash::Hash,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: EguiPrompting + std::default::Default,
 {
     fn build_gui(
         &mut self,
@@ -760,32 +2121,243 @@ where
         name: Option<&str>,
         comment: Option<&str>,
     ) -> Result<(), String> {
-        if let Some(comment) = comment {
-            ui.label(comment);
-        }
-        if let Some(n) = name {
-            ui.label(n);
-        }
-        for (i, e) in self.iter_mut().enumerate() {
-            let name2 = if let Some(n) = name {
-                format!("{}/{}", n, i + 1)
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        self.check_with(name, &PromptOptions::default())
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        map_edit(self, ui, name, comment, options)
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        map_check(self, name, options)
+    }
+
+    fn is_nested_form(&self) -> bool {
+        true
+    }
+}
+
+/// Edited the same way as a HashMap, with the entries in the order of their keys.
+#[cfg(feature = "egui")]
+impl<K, V> EguiPrompting for std::collections::BTreeMap<K, V>
+where
+    K: std::str::FromStr + std::fmt::Display + Clone + Ord,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: EguiPrompting + std::default::Default,
+{
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        self.check_with(name, &PromptOptions::default())
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        map_edit(self, ui, name, comment, options)
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        map_check(self, name, options)
+    }
+
+    fn is_nested_form(&self) -> bool {
+        true
+    }
+}
+
+/// A change to the list of elements of a Vec requested in its egui form
+#[cfg(feature = "egui")]
+enum VecAction {
+    /// Move the element up one place
+    Up(usize),
+    /// Move the element down one place
+    Down(usize),
+    /// Insert a copy of the element after it
+    Duplicate(usize),
+    /// Remove the element
+    Remove(usize),
+}
+
+/// Show the elements of a Vec with buttons to move and remove each of them, and to duplicate it when a duplicate function is given.
+/// Any error for an element is shown under it, and elements that are forms of their own are shown in sections that can be collapsed.
+#[cfg(feature = "egui")]
+fn build_vec_gui<T: EguiPrompting + Default>(
+    v: &mut Vec<T>,
+    ui: &mut egui::Ui,
+    name: Option<&str>,
+    comment: Option<&str>,
+    options: &PromptOptions,
+    duplicate: Option<fn(&T) -> T>,
+) -> Result<(), String> {
+    form::labels(ui, name, comment);
+    let len = v.len();
+    let can_add = options.max_len.is_none_or(|m| len < m);
+    let can_remove = options.min_len.is_none_or(|m| len > m);
+    let element_options = options.for_elements();
+    let mut action = None;
+    let mut result = Ok(());
+    for (i, e) in v.iter_mut().enumerate() {
+        let name2 = if let Some(n) = name {
+            format!("{}/{}", n, i + 1)
+        } else {
+            format!("{}", i + 1)
+        };
+        ui.push_id(i, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}.", i + 1));
+                if ui.add_enabled(i > 0, egui::Button::new("⬆")).on_hover_text("Move up").clicked() {
+                    action = Some(VecAction::Up(i));
+                }
+                if ui.add_enabled(i + 1 < len, egui::Button::new("⬇")).on_hover_text("Move down").clicked() {
+                    action = Some(VecAction::Down(i));
+                }
+                if duplicate.is_some() && ui.add_enabled(can_add, egui::Button::new("Duplicate")).clicked() {
+                    action = Some(VecAction::Duplicate(i));
+                }
+                if ui.add_enabled(can_remove, egui::Button::new("Remove")).clicked() {
+                    action = Some(VecAction::Remove(i));
+                }
+            });
+            let r = if e.is_nested_form() {
+                form::section(ui, &name2, &format!("Element {}", i + 1), None, |ui| {
+                    e.build_gui_with(ui, Some(&name2), None, &element_options)
+                        .and_then(|_| e.check_with(Some(&name2), &element_options))
+                })
             } else {
-                format!("{}", i + 1)
+                form::field(ui, &name2, |ui| {
+                    e.build_gui_with(ui, Some(&name2), None, &element_options)
+                        .and_then(|_| e.check_with(Some(&name2), &element_options))
+                })
             };
-            let _ = e.build_gui(ui, Some(&name2), None);
+            if result.is_ok() {
+                result = r;
+            }
+        });
+    }
+    match action {
+        Some(VecAction::Up(i)) => v.swap(i - 1, i),
+        Some(VecAction::Down(i)) => v.swap(i, i + 1),
+        Some(VecAction::Duplicate(i)) => {
+            if let Some(duplicate) = duplicate {
+                let e = duplicate(&v[i]);
+                v.insert(i + 1, e);
+            }
         }
-        if ui.button("Add another").clicked() {
-            self.push(T::default());
+        Some(VecAction::Remove(i)) => {
+            v.remove(i);
         }
-        Ok(())
+        None => {}
+    }
+    if ui.add_enabled(can_add, egui::Button::new("Add another")).clicked() {
+        v.push(T::default());
+    }
+    if let Err(err) = options.check_len(v.len()) {
+        form::report_error(ui, name.unwrap_or(""), &err);
+        if result.is_ok() {
+            result = Err(format!("{} {}", name.unwrap_or(""), err));
+        }
+    }
+    result
+}
+
+/// Each element has buttons to move it and remove it, and any error for an element is shown under it.
+/// Elements that are forms of their own are shown in sections that can be collapsed.
+/// The options are passed on to each element, except min_len and max_len which limit the number of elements.
+/// Elements that can be cloned can also be duplicated, see [`EguiDuplicate`].
+#[cfg(feature = "egui")]
+impl<T> EguiPrompting for Vec<T>
+where
+    T: EguiPrompting + std::default::Default,
+{
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
     }
 
     fn check(&self, name: Option<&str>,) -> Result<(), String> {
+        self.check_with(name, &PromptOptions::default())
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        build_vec_gui(self, ui, name, comment, options, None)
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        options
+            .check_len(self.len())
+            .map_err(|e| format!("{} {}", name.unwrap_or(""), e))?;
+        let element_options = options.for_elements();
         for i in self {
-            i.check(name)?;
+            i.check_with(name, &element_options)?;
         }
         Ok(())
     }
+
+    fn is_nested_form(&self) -> bool {
+        true
+    }
+}
+
+/// Shows a list or map in an egui form with a button to duplicate its elements, for elements that can be cloned.
+/// The derive macro uses this for a field with the attribute `#[prompt(duplicate)]`.
+#[cfg(feature = "egui")]
+pub trait EguiDuplicate {
+    /// Build the input form like build_gui_with, with a button to duplicate elements
+    fn build_gui_duplicate(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String>;
+}
+
+#[cfg(feature = "egui")]
+impl<T> EguiDuplicate for Vec<T>
+where
+    T: EguiPrompting + std::default::Default + Clone,
+{
+    fn build_gui_duplicate(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        build_vec_gui(self, ui, name, comment, options, Some(T::clone))
+    }
 }
 
 #[cfg(feature = "egui")]
@@ -798,12 +2370,25 @@ where
         ui: &mut egui::Ui,
         name: Option<&str>,
         comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
+    }
+
+    fn check(&self, name: Option<&str>,) -> Result<(), String> {
+        self.check_with(name, &PromptOptions::default())
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
     ) -> Result<(), String> {
         let mut checked = self.is_some();
-        if let Some(comment) = comment {
-            ui.label(comment);
-        }
-        if ui.checkbox(&mut checked, name.unwrap_or("Item")).changed() {
+        let shown = form::labels(ui, None, comment);
+        let cname = if shown { name.unwrap_or("Item") } else { "" };
+        if ui.checkbox(&mut checked, cname).changed() {
             if checked {
                 *self = Some(T::default());
             } else {
@@ -811,13 +2396,24 @@ where
             }
         }
         if let Some(thing) = self {
-            thing.build_gui(ui, name, comment)?;
+            if shown {
+                thing.build_gui_with(ui, name, comment, options)?;
+            } else {
+                form::without_labels(ui, |ui| thing.build_gui_with(ui, name, comment, options))?;
+            }
         }
         Ok(())
     }
 
-    fn check(&self, _name: Option<&str>,) -> Result<(), String> {
-        Ok(())
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        match self {
+            Some(thing) => thing.check_with(name, options),
+            None => Ok(()),
+        }
+    }
+
+    fn is_nested_form(&self) -> bool {
+        self.as_ref().is_some_and(|t| t.is_nested_form())
     }
 }
 
@@ -835,43 +2431,94 @@ where
         self.as_mut().build_gui(ui, name, comment)
     }
 
-    fn check(&self, _name: Option<&str>,) -> Result<(), String> {
-        Ok(())
+    fn check(&self, name: Option<&str>,) -> Result<(), String> {
+        self.as_ref().check(name)
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        self.as_mut().build_gui_with(ui, name, comment, options)
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        self.as_ref().check_with(name, options)
+    }
+
+    fn is_nested_form(&self) -> bool {
+        self.as_ref().is_nested_form()
     }
 }
 
-#[derive(Debug, Clone, Default)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String"))]
 /// This is a type that allows a user to enter a passowrd without revealing that password onscreen.
-pub struct Password(String);
+/// The password is erased from memory when dropped, and is not shown by Debug or Display.
+/// Use expose_secret to get the password.
+pub struct Password(zeroize::Zeroizing<String>);
 
 impl From<Password> for String {
-    fn from(value: Password) -> Self {
-        value.0
+    fn from(mut value: Password) -> Self {
+        std::mem::take(&mut *value.0)
+    }
+}
+
+impl From<String> for Password {
+    fn from(value: String) -> Self {
+        Password::new(value)
+    }
+}
+
+impl PartialEq for Password {
+    fn eq(&self, other: &Self) -> bool {
+        *self.0 == *other.0
+    }
+}
+
+impl std::fmt::Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Password(<redacted>)")
     }
 }
 
 impl std::fmt::Display for Password {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.0)
+        f.write_str("<redacted>")
     }
 }
 
-impl std::ops::Deref for Password {
-    type Target = String;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+/// The password is only serialized when the serde-secrets feature is enabled.
+/// Otherwise use serialize_secret on individual fields that should be written out.
+#[cfg(feature = "serde-secrets")]
+impl serde::Serialize for Password {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.expose_secret())
     }
 }
 
-impl std::ops::DerefMut for Password {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+/// Read a password from the user, showing the mask character from the options for each character typed
+fn read_password(options: &PromptOptions) -> Result<String, Error> {
+    match options.mask {
+        Some(mask) => timeout::read_masked(mask),
+        None => timeout::read_secret(),
     }
 }
 
 impl Prompting for Password {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<Self, Error> {
         use std::io::Write;
         if let Some(comment) = comment {
             println!("{}", comment);
@@ -880,51 +2527,84 @@ impl Prompting for Password {
             print!("{}: ", n);
             std::io::stdout().flush().unwrap();
         }
-        let buffer = rpassword::read_password().unwrap();
-        Ok(Password(buffer))
+        let buffer = read_password(options)?;
+        Ok(Password::new(buffer))
     }
 }
 
 impl Password {
     /// Construct a new password.
     pub fn new(s: String) -> Self {
-        Password(s)
+        Password(zeroize::Zeroizing::new(s))
+    }
+
+    /// Get the password
+    pub fn expose_secret(&self) -> &str {
+        &self.0
     }
 }
 
-#[derive(Debug, Clone, Default)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String"))]
 /// This is a type that allows a user to create a password, prompting them once for the password, and a second time to verify the password.
-pub struct Password2(String, String);
+/// The password is erased from memory when dropped, and is not shown by Debug or Display.
+/// Use expose_secret to get the password.
+pub struct Password2(zeroize::Zeroizing<String>, zeroize::Zeroizing<String>);
 
 impl From<Password2> for String {
-    fn from(value: Password2) -> Self {
-        value.0
+    fn from(mut value: Password2) -> Self {
+        std::mem::take(&mut *value.0)
     }
 }
 
-impl std::fmt::Display for Password2 {
+impl From<String> for Password2 {
+    fn from(value: String) -> Self {
+        Password2::new(value)
+    }
+}
+
+impl PartialEq for Password2 {
+    fn eq(&self, other: &Self) -> bool {
+        *self.0 == *other.0 && *self.1 == *other.1
+    }
+}
+
+impl std::fmt::Debug for Password2 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.0)
+        f.write_str("Password2(<redacted>)")
     }
 }
 
-impl std::ops::Deref for Password2 {
-    type Target = String;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl std::fmt::Display for Password2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
     }
 }
 
-impl std::ops::DerefMut for Password2 {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+/// The password is only serialized when the serde-secrets feature is enabled.
+/// Otherwise use serialize_secret on individual fields that should be written out.
+#[cfg(feature = "serde-secrets")]
+impl serde::Serialize for Password2 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.expose_secret())
     }
 }
 
 impl Prompting for Password2 {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<Self, Error> {
         use std::io::Write;
+        if let Some(comment) = comment {
+            println!("{}", comment);
+        }
         let mut buffer;
         loop {
             if let Some(n) = name {
@@ -933,18 +2613,27 @@ impl Prompting for Password2 {
                 print!("Enter password:");
             }
             std::io::stdout().flush().unwrap();
-            buffer = rpassword::read_password().unwrap();
+            buffer = zeroize::Zeroizing::new(read_password(options)?);
             if let Some(n) = name {
                 print!("{}: Enter password again:", n);
             } else {
                 print!("Enter password again: ");
             }
             std::io::stdout().flush().unwrap();
-            let buf2 = rpassword::read_password().unwrap();
-            if buffer == buf2 {
-                break;
+            let buf2 = zeroize::Zeroizing::new(read_password(options)?);
+            if buffer != buf2 {
+                println!("Passwords do not match, try again");
+                continue;
+            }
+            match options.password_policy.as_ref().map(|p| p.check(&buffer)) {
+                Some(Err(reasons)) => {
+                    println!("That password is not acceptable, try again");
+                    for r in reasons {
+                        println!("\tThe password {}", r);
+                    }
+                }
+                _ => break,
             }
-            println!("Passwords do not match, try again");
         }
         Ok(Password2(buffer.clone(), buffer))
     }
@@ -953,9 +2642,15 @@ impl Prompting for Password2 {
 impl Password2 {
     /// Construct a new password
     pub fn new(s: String) -> Self {
+        let s = zeroize::Zeroizing::new(s);
         Password2(s.clone(), s)
     }
 
+    /// Get the password
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
     /// Get a mutable reference to the second copy of the password
     pub fn second(&mut self) -> &mut String {
         &mut self.1
@@ -965,15 +2660,232 @@ impl Password2 {
     pub fn matches(&self) -> bool {
         self.0 == self.1
     }
+
+    /// Check the password against a policy, returning the reasons that it is not acceptable
+    pub fn check_policy(&self, policy: &PasswordPolicy) -> Result<(), Vec<String>> {
+        policy.check(&self.0)
+    }
+
+    /// Check the password against the policy of the options, if there is one
+    #[cfg(any(feature = "egui", feature = "html", feature = "tui"))]
+    pub(crate) fn check_options(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        match &options.password_policy {
+            Some(p) => self.check_policy(p).map_err(|reasons| {
+                format!("{} password {}", name.unwrap_or(""), reasons.join(", "))
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Serialize a password in plain text, for use with `#[serde(serialize_with = "userprompt::serialize_secret")]`
+/// on the fields of Password or Password2 that should be written out.
+#[cfg(feature = "serde")]
+pub fn serialize_secret<T, S>(secret: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Secret,
+    S: serde::Serializer,
+{
+    serializer.serialize_str(secret.expose_secret())
+}
+
+/// A type holding a secret that can be exposed on request
+pub trait Secret {
+    /// Get the secret
+    fn expose_secret(&self) -> &str;
+}
+
+impl Secret for Password {
+    fn expose_secret(&self) -> &str {
+        Password::expose_secret(self)
+    }
+}
+
+impl Secret for Password2 {
+    fn expose_secret(&self) -> &str {
+        Password2::expose_secret(self)
+    }
 }
 
 /// The types of errors that can occur when prompting for user input.
+/// More kinds of errors may be added, so matches on this must have a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Error in standard input
     InputError(std::io::Error),
     /// Error converting from string to the desired type
     ConversionError,
+    /// The user cancelled the prompt
+    Cancelled,
+    /// No input was received before the timeout expired
+    Timeout,
+}
+
+/// Options that change how a single field is prompted for, usually given by the prompt attribute of the derive macros.
+/// Options that do not apply to a type are ignored.
+///
+/// # The prompt attribute
+/// Every derive macro of this crate accepts `#[prompt(...)]` on the fields of a struct or enum, with any of these options
+/// separated by commas. A mistake in the attribute is a compile error.
+///
+/// | Option | Applies to | Effect |
+/// |---|---|---|
+/// | `policy(min_length = 12, require_digit, reject_common)` | Password2 | The password policy, see [`PasswordPolicy`] for its fields |
+/// | `mask = '*'` | Password, Password2 | The character echoed for each character typed on the command line |
+/// | `reveal` | Password, Password2 | Adds a button to show the password in graphical forms |
+/// | `min = 0, max = 100` | numbers | Limits a number to a range, shown as a slider in graphical forms, or a clamped drag value with only one of them |
+/// | `step = 5` | numbers | The step of the slider in graphical forms |
+/// | `unit = "%"` | numbers | The unit shown with the number |
+/// | `schemes("https")` | Url | The schemes allowed for a URL |
+/// | `default_port = 443` | SocketAddr, HostPort | The port used for an address entered without one |
+/// | `overwrite = "ask"` | FileCreate | Whether an existing file is rejected, replaced once confirmed, or replaced, see [`Overwrite`] |
+/// | `enforce_extension` | FileOpen, FileCreate | Requires a file to be opened to have an extension of the filter, and adds it to a file to be created |
+/// | `validate = check_pem` | FileOpen | Checks the file with a function taking `&Path` and returning `Result<(), String>` |
+/// | `filter("Images", ["png", "jpg"])` | files and directories | The filter of the file dialog, shown on the command line |
+/// | `title = "Pick a logo"` | files and directories | The title of the file dialog, shown on the command line |
+/// | `initial_dir = "logos"` | files and directories | The directory the file dialog starts in, relative paths entered on the command line are in it |
+/// | `initial_file = "logo.png"` | files | The file the file dialog starts with, used when nothing is entered on the command line |
+/// | `base_dir = "~/data"` | files and directories | The directory relative paths entered on the command line are in, instead of the initial directory |
+/// | `canonicalize` | files and directories | Makes paths entered on the command line absolute |
+/// | `min_len = 1, max_len = 5` | Vec | Limits the number of elements in a list |
+///
+/// A leading `~` and environment variables such as `$HOME` are expanded in all of the paths.
+///
+/// A few options are handled by the derive macros themselves rather than kept in these options.
+/// `timeout_secs = 30` gives a field a time limit on the command line and when prompting asynchronously,
+/// after which the field takes its default value. `duplicate` adds a button to duplicate each element of a list in egui,
+/// for elements that can be cloned. On the struct or enum itself, `#[prompt(layout = "grid")]` shows each field of an egui form
+/// as a row of a grid, and `layout = "vertical"` keeps the default of one field after another.
+#[derive(Clone, Debug, Default)]
+pub struct PromptOptions {
+    /// The requirements for a new password
+    pub password_policy: Option<PasswordPolicy>,
+    /// The character shown on the command line for each character of a password as it is typed, None to show nothing
+    pub mask: Option<char>,
+    /// Show a button to reveal a password in graphical forms
+    pub reveal: bool,
+    /// The smallest value allowed for a number
+    pub min: Option<f64>,
+    /// The largest value allowed for a number
+    pub max: Option<f64>,
+    /// The step between values of a number in graphical forms
+    pub step: Option<f64>,
+    /// The unit shown after a number, such as "ms" or "%"
+    pub unit: Option<String>,
+    /// The schemes allowed for a URL, such as "https", empty to allow any scheme
+    pub schemes: Vec<String>,
+    /// The port used for a network address when none is entered
+    pub default_port: Option<u16>,
+    /// What to do when a file to be created already exists
+    pub overwrite: Option<Overwrite>,
+    /// Require the extension of the filter for a file to be opened, or add it to a file to be created that has none of its extensions
+    pub enforce_extension: bool,
+    /// The check of the contents of a file to be opened
+    pub validator: Option<FileValidator>,
+    /// The filter of a file dialog, a description and the extensions allowed
+    pub filter: Option<(String, Vec<String>)>,
+    /// The title of a file dialog, also shown before a path is entered on the command line
+    pub title: Option<String>,
+    /// The directory a file dialog starts in, relative paths entered on the command line are in this directory
+    pub initial_dir: Option<std::path::PathBuf>,
+    /// The file name a file dialog starts with, used when nothing is entered on the command line
+    pub initial_file: Option<String>,
+    /// The directory that relative paths entered on the command line are in, instead of the initial directory
+    pub base_dir: Option<std::path::PathBuf>,
+    /// Make paths entered on the command line absolute, without any symbolic links
+    pub canonicalize: bool,
+    /// The smallest number of elements allowed in a list
+    pub min_len: Option<usize>,
+    /// The largest number of elements allowed in a list
+    pub max_len: Option<usize>,
+}
+
+impl PromptOptions {
+    /// Show the title and the filter of a file dialog on the command line
+    pub(crate) fn print_dialog(&self) {
+        if let Some(t) = &self.title {
+            println!("{}", t);
+        }
+        if let Some((desc, exts)) = &self.filter {
+            println!("{} ({})", desc, exts.join(", "));
+        }
+    }
+
+    /// Expand a path typed on the command line, putting a relative path in the base directory, or else the initial directory
+    pub fn resolve_path(&self, typed: &str) -> std::path::PathBuf {
+        let base = self.base_dir.as_deref().or(self.initial_dir.as_deref());
+        path::resolve(typed, base, self.canonicalize)
+    }
+
+    /// Prompt for a path on the command line, using the initial file when nothing is entered.
+    /// Returns the path as typed and the expanded path.
+    pub(crate) fn prompt_path(&self, name: Option<&str>, comment: Option<&str>) -> Result<(String, std::path::PathBuf), Error> {
+        self.print_dialog();
+        if let Some(f) = &self.initial_file {
+            println!("Leave empty to use {}", f);
+        }
+        let mut typed = <String as Prompting>::prompt_generic::<String>(name, comment)?;
+        if typed.is_empty() {
+            if let Some(f) = &self.initial_file {
+                typed = f.clone();
+            }
+        }
+        let pb = self.resolve_path(&typed);
+        Ok((typed, pb))
+    }
+
+    /// The options for the elements of a list, without the limits on the number of elements of the list itself
+    pub(crate) fn for_elements(&self) -> Self {
+        Self {
+            min_len: None,
+            max_len: None,
+            ..self.clone()
+        }
+    }
+
+    /// Check that a list has an allowed number of elements, returning a message describing the allowed number if not
+    pub fn check_len(&self, len: usize) -> Result<(), String> {
+        match (self.min_len, self.max_len) {
+            (Some(min), _) if len < min => Err(format!("must have at least {} elements", min)),
+            (_, Some(max)) if len > max => Err(format!("must have at most {} elements", max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that a number is within the minimum and maximum, returning a message describing the allowed range if not
+    pub fn check_range(&self, v: f64) -> Result<(), String> {
+        let unit = |n: f64| match &self.unit {
+            Some(u) => format!("{} {}", n, u),
+            None => n.to_string(),
+        };
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if v < min || v > max => {
+                Err(format!("must be between {} and {}", unit(min), unit(max)))
+            }
+            (Some(min), None) if v < min => Err(format!("must be at least {}", unit(min))),
+            (None, Some(max)) if v > max => Err(format!("must be at most {}", unit(max))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Prompt for a value with a parser, showing the message from the parser and asking again when the input is not valid
+pub(crate) fn prompt_parsed<T>(
+    name: Option<&str>,
+    comment: Option<&str>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<T, Error> {
+    if let Some(comment) = comment {
+        println!("{}", comment);
+    }
+    loop {
+        let s = String::prompt(name, None)?;
+        match parse(s.trim()) {
+            Ok(v) => return Ok(v),
+            Err(e) => println!("{}", e),
+        }
+    }
 }
 
 /// This trait is responsible for doing the work of prompting the user for input.
@@ -984,6 +2896,16 @@ pub trait Prompting: Sized {
     /// * comment - The optional comment to show to the user to explain the field being entered
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error>;
 
+    /// prompt for input of the specified type, using options usually given by the prompt attribute of the derive macro.
+    /// Types that have no options prompt the same way as prompt.
+    fn prompt_with(
+        name: Option<&str>,
+        comment: Option<&str>,
+        _options: &PromptOptions,
+    ) -> Result<Self, Error> {
+        Self::prompt(name, comment)
+    }
+
     fn prompt_generic<T>(name: Option<&str>, comment: Option<&str>) -> Result<T, Error>
     where
         T: Prompting + core::str::FromStr,
@@ -1003,16 +2925,13 @@ pub trait Prompting: Sized {
 }
 
 impl Prompting for String {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+    fn prompt(name: Option<&str>, _comment: Option<&str>) -> Result<Self, Error> {
         use std::io::Write;
-        let mut buffer = String::new();
         if let Some(n) = name {
             print!("{}: ", n);
             std::io::stdout().flush().unwrap();
         }
-        std::io::stdin()
-            .read_line(&mut buffer)
-            .map_err(|e| Error::InputError(e))?;
+        let mut buffer = timeout::read_line()?;
         loop {
             if buffer.ends_with('\n') {
                 buffer.pop();
@@ -1028,71 +2947,91 @@ impl Prompting for String {
     }
 }
 
-impl Prompting for u8 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
-}
-
-impl Prompting for i8 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
-}
-
-impl Prompting for u16 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
-}
-
-impl Prompting for i16 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
-}
-
-impl Prompting for u32 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
-}
+/// Implements Prompting for numbers.
+/// The options can limit the number to a range, and give a unit that is shown with the name.
+macro_rules! prompting_number {
+    ($($t:ty),*) => {
+        $(
+            impl Prompting for $t {
+                fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+                    Self::prompt_generic::<Self>(name, comment)
+                }
 
-impl Prompting for i32 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
+                fn prompt_with(
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                    options: &PromptOptions,
+                ) -> Result<Self, Error> {
+                    let name = match &options.unit {
+                        Some(u) => Some(format!("{} ({})", name.unwrap_or(""), u)),
+                        None => name.map(|n| n.to_string()),
+                    };
+                    let mut comment = comment;
+                    loop {
+                        let v = Self::prompt_generic::<Self>(name.as_deref(), comment.take())?;
+                        match options.check_range(v as f64) {
+                            Ok(()) => return Ok(v),
+                            Err(e) => println!("The value {}", e),
+                        }
+                    }
+                }
+            }
+        )*
+    };
 }
 
-impl Prompting for u64 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
-}
+prompting_number!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
 
-impl Prompting for i64 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
+/// Implements Prompting for types that are parsed from a string.
+/// The parsing rejects zero for the NonZero types.
+macro_rules! prompting_parsed {
+    ($($t:ty),*) => {
+        $(
+            impl Prompting for $t {
+                fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+                    Self::prompt_generic::<Self>(name, comment)
+                }
+            }
+        )*
+    };
 }
 
-impl Prompting for usize {
+/// A char is exactly one Unicode scalar value, which is not always a whole character as the user sees it.
+/// A letter typed as a base letter followed by a combining accent is two scalar values, and is asked for again.
+impl Prompting for char {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
+        if let Some(comment) = comment {
+            println!("{}", comment);
+        }
+        loop {
+            let s = <String as Prompting>::prompt(name, None)?;
+            match s.parse() {
+                Ok(c) => return Ok(c),
+                Err(_) => println!("{}", char_error(&s)),
+            }
+        }
     }
 }
 
-impl Prompting for f32 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
+/// The message shown when the text entered for a char is not a single Unicode scalar value
+pub(crate) fn char_error(s: &str) -> String {
+    format!("Enter exactly one Unicode scalar value, {} were entered", s.chars().count())
 }
 
-impl Prompting for f64 {
-    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
-    }
-}
+prompting_parsed!(
+    std::num::NonZeroU8,
+    std::num::NonZeroI8,
+    std::num::NonZeroU16,
+    std::num::NonZeroI16,
+    std::num::NonZeroU32,
+    std::num::NonZeroI32,
+    std::num::NonZeroU64,
+    std::num::NonZeroI64,
+    std::num::NonZeroU128,
+    std::num::NonZeroI128,
+    std::num::NonZeroUsize,
+    std::num::NonZeroIsize
+);
 
 impl Prompting for bool {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
@@ -1105,28 +3044,31 @@ impl Prompting for bool {
             std::io::stdout().flush().unwrap();
         }
         loop {
-            let s = Self::prompt_generic::<String>(None, None);
-            match s {
-                Ok(s) => match s.to_ascii_lowercase().as_str() {
-                    "yes" | "true" => {
-                        return Ok(true);
-                    }
-                    "no" | "false" => {
-                        return Ok(false);
-                    }
-                    _ => {
-                        print!("Invalid input");
-                    }
-                },
-                _ => {}
+            let s = Self::prompt_generic::<String>(None, None)?;
+            match s.to_ascii_lowercase().as_str() {
+                "yes" | "true" => {
+                    return Ok(true);
+                }
+                "no" | "false" => {
+                    return Ok(false);
+                }
+                _ => {
+                    print!("Invalid input");
+                }
             }
         }
     }
 }
 
+/// A leading `~` and environment variables in the path are expanded, see [`path::expand`].
 impl Prompting for std::path::PathBuf {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        Self::prompt_generic::<Self>(name, comment)
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
+        let typed = Self::prompt_generic::<String>(name, comment)?;
+        Ok(options.resolve_path(&typed))
     }
 }
 
@@ -1147,44 +3089,51 @@ where
         loop {
             print!("Enter key name (blank to end):");
             std::io::stdout().flush().unwrap();
-            let key = String::prompt(None, None).unwrap();
+            let key = String::prompt(None, None)?;
             if key.is_empty() {
                 println!("Done");
                 break;
             }
-            let t = T::prompt(None, None).unwrap();
+            if let Err(e) = hm.check_key(&key) {
+                println!("{}", e);
+                continue;
+            }
+            let t = T::prompt(None, None)?;
             hm.map.insert(key, t);
         }
         Ok(hm)
     }
 }
 
-impl<T> Prompting for std::collections::HashMap<String, T>
+/// The options are passed on to each value.
+impl<K, V> Prompting for std::collections::HashMap<K, V>
 where
-    T: Prompting,
+    K: std::str::FromStr + std::fmt::Display + Clone + Eq + std::hash::Hash,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: Prompting,
 {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
-        use std::io::Write;
-        if let Some(comment) = comment {
-            println!("{}", comment);
-        }
-        if let Some(n) = name {
-            print!("{}: ", n);
-            std::io::stdout().flush().unwrap();
-        }
-        let mut hm = std::collections::HashMap::new();
-        loop {
-            print!("Enter key name (blank to end):");
-            std::io::stdout().flush().unwrap();
-            let key = String::prompt(None, None).unwrap();
-            if key.is_empty() {
-                println!("Done");
-                break;
-            }
-            let t = T::prompt(None, None).unwrap();
-            hm.insert(key, t);
-        }
-        Ok(hm)
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
+        prompt_map(name, comment, options)
+    }
+}
+
+/// The options are passed on to each value.
+impl<K, V> Prompting for std::collections::BTreeMap<K, V>
+where
+    K: std::str::FromStr + std::fmt::Display + Clone + Ord,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: Prompting,
+{
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
+        prompt_map(name, comment, options)
     }
 }
 
@@ -1203,25 +3152,27 @@ impl<T> VecOption<T> {
 
 impl<T: Prompting> Prompting for VecOption<T> {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
         println!("Provide an element? (yes/no)]");
         let v = bool::prompt(None, None)?;
         if v {
-            match T::prompt(name, comment) {
-                Ok(t) => {
-                    return Ok(VecOption::new(Some(t)));
-                }
-                Err(e) => {
-                    return Err(e);
-                }
-            }
+            T::prompt_with(name, comment, options).map(|t| VecOption::new(Some(t)))
         } else {
             Ok(VecOption::new(None))
         }
     }
 }
 
+/// The options are passed on to each element, except min_len and max_len which limit the number of elements.
 impl<T: Prompting> Prompting for Vec<T> {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
         let mut built = Vec::new();
         if let Some(comment) = comment {
             println!("{}", comment);
@@ -1229,13 +3180,18 @@ impl<T: Prompting> Prompting for Vec<T> {
         if let Some(name) = name {
             println!("Enter a list of items for {}", name);
         }
-        loop {
+        let element_options = options.for_elements();
+        while options.max_len.is_none_or(|m| built.len() < m) {
             let name2 = if let Some(n) = name {
                 format!("{}/element{}", n, built.len() + 1)
             } else {
                 format!("element{}", built.len() + 1)
             };
-            let v: VecOption<T> = <VecOption<T> as Prompting>::prompt(Some(&name2), None)?;
+            if options.min_len.is_some_and(|m| built.len() < m) {
+                built.push(T::prompt_with(Some(&name2), None, &element_options)?);
+                continue;
+            }
+            let v: VecOption<T> = <VecOption<T> as Prompting>::prompt_with(Some(&name2), None, &element_options)?;
             match v.inner {
                 None => break,
                 Some(v) => {
@@ -1252,19 +3208,16 @@ where
     T: Prompting,
 {
     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
         if let Some(name) = name {
             println!("[{} is optional, provide? (yes/no)]", name);
         }
         let v = bool::prompt(name, None)?;
         if v {
-            match T::prompt(name, comment) {
-                Ok(t) => {
-                    return Ok(Some(t));
-                }
-                Err(e) => {
-                    return Err(e);
-                }
-            }
+            T::prompt_with(name, comment, options).map(Some)
         } else {
             Ok(None)
         }
@@ -1279,4 +3232,147 @@ where
         let a: T = T::prompt(name, comment)?;
         Ok(Box::new(a))
     }
+
+    fn prompt_with(name: Option<&str>, comment: Option<&str>, options: &PromptOptions) -> Result<Self, Error> {
+        let a: T = T::prompt_with(name, comment, options)?;
+        Ok(Box::new(a))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Make an empty directory for a test, containing the given files
+    fn test_dir(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("userprompt-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for f in files {
+            std::fs::write(dir.join(f), "").unwrap();
+        }
+        dir
+    }
+
+    /// The names of the files in a list
+    fn names(pbs: &[PathBuf]) -> Vec<String> {
+        pbs.iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// An element type that cannot be cloned
+    #[cfg(feature = "egui")]
+    #[derive(Default)]
+    struct Unique(u8);
+
+    #[cfg(feature = "egui")]
+    impl EguiPrompting for Unique {
+        fn build_gui(&mut self, ui: &mut egui::Ui, name: Option<&str>, comment: Option<&str>) -> Result<(), String> {
+            self.0.build_gui(ui, name, comment)
+        }
+
+        fn check(&self, _name: Option<&str>) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn vec_limits_apply_to_the_list_only() {
+        let options = PromptOptions {
+            min_len: Some(1),
+            max_len: Some(2),
+            ..Default::default()
+        };
+        let v: Vec<Vec<Unique>> = vec![Vec::new(), Vec::new()];
+        assert_eq!(v.check_with(None, &options), Ok(()));
+        let v: Vec<Vec<Unique>> = Vec::new();
+        assert!(v.check_with(None, &options).is_err());
+        let v: Vec<Vec<Unique>> = vec![Vec::new(), Vec::new(), Vec::new()];
+        assert!(v.check_with(None, &options).is_err());
+    }
+
+    #[test]
+    fn create_in_writable_directory() {
+        let dir = test_dir("create", &["old.txt"]);
+        let f = FileCreate::from(dir.join("new.txt"));
+        assert_eq!(f.check_path(Overwrite::Reject), Ok(()));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        let f = FileCreate::from(dir.join("old.txt"));
+        assert!(f.check_path(Overwrite::Reject).is_err());
+        assert!(f.check_path(Overwrite::Ask).is_err());
+        assert_eq!(f.check_path(Overwrite::Allow), Ok(()));
+        assert!(FileCreate::from(dir.clone()).check_path(Overwrite::Allow).is_err());
+        let f = FileCreate::from(dir.join("missing").join("new.txt"));
+        assert!(f.check_path(Overwrite::Allow).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn files_separated_by_spaces_and_commas() {
+        let dir = test_dir("separators", &["a.txt", "b.txt", "c.txt"]);
+        let pbs = FilesOpen::parse_in("a.txt b.txt,c.txt", Some(&dir)).unwrap();
+        assert_eq!(names(&pbs), ["a.txt", "b.txt", "c.txt"]);
+        let pbs = FilesOpen::parse_in("  c.txt ,, a.txt  c.txt ", Some(&dir)).unwrap();
+        assert_eq!(names(&pbs), ["c.txt", "a.txt"]);
+        assert!(FilesOpen::parse_in(" , ", Some(&dir)).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn quoted_files() {
+        let dir = test_dir("quoted", &["a b.txt", "c,d.txt", "e.txt"]);
+        let pbs = FilesOpen::parse_in("\"a b.txt\" \"c,d.txt\",e.txt", Some(&dir)).unwrap();
+        assert_eq!(names(&pbs), ["a b.txt", "c,d.txt", "e.txt"]);
+        let pbs = FilesOpen::parse_in("a\" \"b.txt", Some(&dir)).unwrap();
+        assert_eq!(names(&pbs), ["a b.txt"]);
+        assert!(FilesOpen::parse_in("a b.txt", Some(&dir)).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn quoting_round_trip() {
+        let dir = test_dir("round-trip", &["a b.txt", "c,d.txt", "e.txt"]);
+        let files = FilesOpen::from(vec![dir.join("a b.txt"), dir.join("c,d.txt"), dir.join("e.txt")]);
+        assert_eq!(FilesOpen::parse(&files.to_line()).unwrap(), *files);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn glob_patterns() {
+        let dir = test_dir("glob", &["a.csv", "b.csv", "c.txt"]);
+        std::fs::create_dir(dir.join("d.csv")).unwrap();
+        let pbs = FilesOpen::parse_in("*.csv c.txt", Some(&dir)).unwrap();
+        assert_eq!(names(&pbs), ["a.csv", "b.csv", "c.txt"]);
+        assert!(FilesOpen::parse_in("*.md", Some(&dir)).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn literal_path_with_glob_characters() {
+        let dir = test_dir("literal", &["report[1].txt", "r.txt"]);
+        let pbs = FilesOpen::parse_in("\"report[1].txt\"", Some(&dir)).unwrap();
+        assert_eq!(names(&pbs), ["report[1].txt"]);
+        let e = FilesOpen::parse_in("report[r].txt", Some(&dir)).unwrap_err();
+        assert!(e.contains("No files match"), "{}", e);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn directories_are_not_files() {
+        let dir = test_dir("directories", &["a.txt"]);
+        std::fs::create_dir(dir.join("sub")).unwrap();
+        assert!(FilesOpen::parse_in("sub", Some(&dir)).is_err());
+        assert!(FilesOpen::parse_in("missing.txt", Some(&dir)).is_err());
+        let files = FilesOpen::from(vec![dir.join("a.txt")]);
+        assert!(files.check_files().is_ok());
+        let files = FilesOpen::from(vec![dir.join("a.txt"), dir.join("sub")]);
+        assert!(files.check_files().is_err());
+        let files = FilesOpen::from(vec![dir.join("missing.txt")]);
+        assert!(files.check_files().is_err());
+        assert!(FilesOpen::from(Vec::new()).check_files().is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,630 @@
+//! Prompting for network addresses, hostnames, and with the url feature, URLs.
+
+use crate::{Error, PromptOptions, Prompting};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// A hostname, such as `example.com`, checked against the rules for names in DNS.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct Hostname(String);
+
+impl Hostname {
+    /// Get the hostname
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::str::FromStr for Hostname {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.strip_suffix('.').unwrap_or(s);
+        if name.is_empty() {
+            return Err("A hostname is required".to_string());
+        }
+        if name.len() > 253 {
+            return Err("A hostname cannot be longer than 253 characters".to_string());
+        }
+        for label in name.split('.') {
+            if label.is_empty() || label.len() > 63 {
+                return Err(
+                    "Each part of a hostname between dots must be 1 to 63 characters long"
+                        .to_string(),
+                );
+            }
+            if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(
+                    "A hostname can only contain letters, digits, hyphens and dots".to_string(),
+                );
+            }
+            if label.starts_with('-') || label.ends_with('-') {
+                return Err("A part of a hostname cannot start or end with a hyphen".to_string());
+            }
+        }
+        Ok(Hostname(name.to_string()))
+    }
+}
+
+impl std::convert::TryFrom<String> for Hostname {
+    type Error = String;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Hostname> for String {
+    fn from(value: Hostname) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for Hostname {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A host and port, such as `example.com:8080` or `[::1]:8080`.
+/// The host is either a hostname or an IP address.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct HostPort {
+    /// The hostname or IP address
+    host: String,
+    /// The port
+    port: u16,
+}
+
+impl HostPort {
+    /// Construct a new host and port
+    pub fn new(host: &str, port: u16) -> Result<Self, String> {
+        let host = host
+            .strip_prefix('[')
+            .and_then(|h| h.strip_suffix(']'))
+            .unwrap_or(host);
+        if host.parse::<IpAddr>().is_err() {
+            host.parse::<Hostname>()?;
+        }
+        if port == 0 {
+            return Err("The port must be a number from 1 to 65535".to_string());
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+        })
+    }
+
+    /// Parse a host and port, using the default port when only a host is given
+    pub fn parse(s: &str, default_port: Option<u16>) -> Result<Self, String> {
+        let s = s.trim();
+        let (host, port) = if let Some(rest) = s.strip_prefix('[') {
+            let (host, rest) = rest
+                .split_once(']')
+                .ok_or_else(|| "An IPv6 address in brackets is missing the ]".to_string())?;
+            if host.parse::<Ipv6Addr>().is_err() {
+                return Err("Only an IPv6 address can be in brackets".to_string());
+            }
+            match rest.strip_prefix(':') {
+                Some(port) => (host, Some(port)),
+                None if rest.is_empty() => (host, None),
+                None => return Err("Only a port can follow an IPv6 address".to_string()),
+            }
+        } else if s.parse::<Ipv6Addr>().is_ok() {
+            (s, None)
+        } else {
+            match s.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (s, None),
+            }
+        };
+        let port = match (port, default_port) {
+            (Some(p), _) => p
+                .parse::<u16>()
+                .map_err(|_| "The port must be a number from 1 to 65535".to_string())?,
+            (None, Some(p)) => p,
+            (None, None) => {
+                return Err("A port is required, such as example.com:8080".to_string());
+            }
+        };
+        Self::new(host, port)
+    }
+
+    /// Get the host
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Get the port
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+impl std::str::FromStr for HostPort {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, None)
+    }
+}
+
+impl std::convert::TryFrom<String> for HostPort {
+    type Error = String;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<HostPort> for String {
+    fn from(value: HostPort) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for HostPort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+}
+
+impl std::net::ToSocketAddrs for HostPort {
+    type Iter = std::vec::IntoIter<SocketAddr>;
+    fn to_socket_addrs(&self) -> std::io::Result<Self::Iter> {
+        (self.host.as_str(), self.port).to_socket_addrs()
+    }
+}
+
+/// Parse a socket address, using the default port when only an IP address is given
+fn parse_socket_addr(s: &str, default_port: Option<u16>) -> Result<SocketAddr, String> {
+    if let Ok(a) = s.parse() {
+        return Ok(a);
+    }
+    match (s.parse::<IpAddr>(), default_port) {
+        (Ok(ip), Some(port)) => Ok(SocketAddr::new(ip, port)),
+        _ => Err(
+            "Not a valid socket address, enter one like 192.168.1.10:8080 or [::1]:8080"
+                .to_string(),
+        ),
+    }
+}
+
+/// Parse a URL, checking that it uses one of the allowed schemes
+#[cfg(feature = "url")]
+fn parse_url(s: &str, schemes: &[String]) -> Result<url::Url, String> {
+    let u = url::Url::parse(s).map_err(|e| format!("Not a valid URL ({})", e))?;
+    check_scheme(&u, schemes)?;
+    Ok(u)
+}
+
+/// Check that a URL uses one of the allowed schemes, any scheme is allowed when there are none
+#[cfg(feature = "url")]
+fn check_scheme(u: &url::Url, schemes: &[String]) -> Result<(), String> {
+    if schemes.is_empty() || schemes.iter().any(|s| s == u.scheme()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "The URL must start with one of: {}",
+            schemes.join(", ")
+        ))
+    }
+}
+
+/// Implements Prompting, EguiPrompting and AsyncPrompting for an address type, with the message shown when it does not parse
+macro_rules! net_parsed {
+    ($($t:ty => $msg:expr),*) => {
+        $(
+            impl Prompting for $t {
+                fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+                    crate::prompt_parsed(name, comment, |s| s.parse().map_err(|_| $msg.to_string()))
+                }
+            }
+
+            #[cfg(feature = "egui")]
+            impl crate::EguiPrompting for $t {
+                fn build_gui(
+                    &mut self,
+                    ui: &mut egui::Ui,
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                ) -> Result<(), String> {
+                    crate::parsed_edit(ui, self, name, comment, |s| s.trim().parse().map_err(|_| $msg.to_string()))
+                }
+
+                fn check(&self, _name: Option<&str>) -> Result<(), String> {
+                    Ok(())
+                }
+            }
+
+            #[cfg(feature = "async")]
+            impl crate::AsyncPrompting for $t {
+                async fn prompt_async<R, W>(
+                    reader: &mut R,
+                    writer: &mut W,
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                ) -> Result<Self, Error>
+                where
+                    R: tokio::io::AsyncRead + Unpin + Send,
+                    W: tokio::io::AsyncWrite + Unpin + Send,
+                {
+                    crate::asynchronous::prompt_parsed_async(reader, writer, name, comment, |s| {
+                        s.parse().map_err(|_| $msg.to_string())
+                    })
+                    .await
+                }
+            }
+        )*
+    };
+}
+
+net_parsed!(
+    IpAddr => "Not a valid IP address, enter one like 192.168.1.10 or ::1",
+    Ipv4Addr => "Not a valid IPv4 address, enter one like 192.168.1.10",
+    Ipv6Addr => "Not a valid IPv6 address, enter one like fe80::1 or ::1"
+);
+
+impl Prompting for Hostname {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        crate::prompt_parsed(name, comment, |s| s.parse())
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::AsyncPrompting for Hostname {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: tokio::io::AsyncRead + Unpin + Send,
+        W: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        crate::asynchronous::prompt_parsed_async(reader, writer, name, comment, |s| s.parse()).await
+    }
+}
+
+#[cfg(feature = "egui")]
+impl crate::EguiPrompting for Hostname {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        crate::parsed_edit(ui, self, name, comment, |s| s.trim().parse())
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        self.0
+            .parse::<Hostname>()
+            .map(|_| ())
+            .map_err(|e| format!("{} {}", name.unwrap_or(""), e))
+    }
+}
+
+impl Prompting for SocketAddr {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<Self, Error> {
+        crate::prompt_parsed(name, comment, |s| {
+            parse_socket_addr(s, options.default_port)
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::AsyncPrompting for SocketAddr {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: tokio::io::AsyncRead + Unpin + Send,
+        W: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        Self::prompt_async_with(reader, writer, name, comment, &PromptOptions::default()).await
+    }
+
+    async fn prompt_async_with<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<Self, Error>
+    where
+        R: tokio::io::AsyncRead + Unpin + Send,
+        W: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        crate::asynchronous::prompt_parsed_async(reader, writer, name, comment, |s| {
+            parse_socket_addr(s, options.default_port)
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "egui")]
+impl crate::EguiPrompting for SocketAddr {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        crate::parsed_edit(ui, self, name, comment, |s| {
+            parse_socket_addr(s.trim(), options.default_port)
+        })
+    }
+}
+
+impl Prompting for HostPort {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<Self, Error> {
+        crate::prompt_parsed(name, comment, |s| HostPort::parse(s, options.default_port))
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::AsyncPrompting for HostPort {
+    async fn prompt_async<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        R: tokio::io::AsyncRead + Unpin + Send,
+        W: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        Self::prompt_async_with(reader, writer, name, comment, &PromptOptions::default()).await
+    }
+
+    async fn prompt_async_with<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<Self, Error>
+    where
+        R: tokio::io::AsyncRead + Unpin + Send,
+        W: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        crate::asynchronous::prompt_parsed_async(reader, writer, name, comment, |s| {
+            HostPort::parse(s, options.default_port)
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "egui")]
+impl crate::EguiPrompting for HostPort {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        HostPort::new(&self.host, self.port)
+            .map(|_| ())
+            .map_err(|e| format!("{} {}", name.unwrap_or(""), e))
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        crate::parsed_edit(ui, self, name, comment, |s| {
+            HostPort::parse(s, options.default_port)
+        })
+    }
+}
+
+#[cfg(feature = "url")]
+impl Prompting for url::Url {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        Self::prompt_with(name, comment, &PromptOptions::default())
+    }
+
+    fn prompt_with(
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<Self, Error> {
+        crate::prompt_parsed(name, comment, |s| parse_url(s, &options.schemes))
+    }
+}
+
+#[cfg(all(feature = "url", feature = "egui"))]
+impl crate::EguiPrompting for url::Url {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_gui_with(ui, name, comment, &PromptOptions::default())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &PromptOptions,
+    ) -> Result<(), String> {
+        crate::parsed_edit(ui, self, name, comment, |s| {
+            parse_url(s.trim(), &options.schemes)
+        })
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &PromptOptions) -> Result<(), String> {
+        check_scheme(self, &options.schemes).map_err(|e| format!("{} {}", name.unwrap_or(""), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostnames() {
+        assert_eq!(
+            "example.com".parse::<Hostname>().unwrap().as_str(),
+            "example.com"
+        );
+        assert_eq!(
+            "example.com.".parse::<Hostname>().unwrap().as_str(),
+            "example.com"
+        );
+        assert_eq!("a-b.c1".parse::<Hostname>().unwrap().as_str(), "a-b.c1");
+        assert!("localhost".parse::<Hostname>().is_ok());
+        let longest = [
+            "a".repeat(63),
+            "b".repeat(63),
+            "c".repeat(63),
+            "d".repeat(61),
+        ]
+        .join(".");
+        assert_eq!(longest.len(), 253);
+        assert!(longest.parse::<Hostname>().is_ok());
+    }
+
+    #[test]
+    fn invalid_hostnames() {
+        for s in [
+            "",
+            ".",
+            "a..b",
+            ".example.com",
+            "-a.com",
+            "a-.com",
+            "exa mple.com",
+            "exa_mple.com",
+            "b\u{fc}cher.de",
+        ] {
+            assert!(s.parse::<Hostname>().is_err(), "{:?}", s);
+        }
+        assert!("a".repeat(64).parse::<Hostname>().is_err());
+        let too_long = [
+            "a".repeat(63),
+            "b".repeat(63),
+            "c".repeat(63),
+            "d".repeat(62),
+        ]
+        .join(".");
+        assert!(too_long.parse::<Hostname>().is_err());
+    }
+
+    #[test]
+    fn host_and_port() {
+        let h = HostPort::parse("example.com:8080", None).unwrap();
+        assert_eq!((h.host(), h.port()), ("example.com", 8080));
+        let h = HostPort::parse(" 192.168.1.10:22 ", None).unwrap();
+        assert_eq!((h.host(), h.port()), ("192.168.1.10", 22));
+        let h = HostPort::parse("example.com:65535", Some(80)).unwrap();
+        assert_eq!(h.port(), 65535);
+    }
+
+    #[test]
+    fn default_port() {
+        let h = HostPort::parse("example.com", Some(443)).unwrap();
+        assert_eq!((h.host(), h.port()), ("example.com", 443));
+        let h = HostPort::parse("10.0.0.1", Some(22)).unwrap();
+        assert_eq!((h.host(), h.port()), ("10.0.0.1", 22));
+        assert!(HostPort::parse("example.com", None).is_err());
+        assert!(HostPort::parse("example.com", Some(0)).is_err());
+    }
+
+    #[test]
+    fn ipv6() {
+        let h = HostPort::parse("[::1]:8080", None).unwrap();
+        assert_eq!((h.host(), h.port()), ("::1", 8080));
+        assert_eq!(h.to_string(), "[::1]:8080");
+        let h = HostPort::parse("[fe80::1]", Some(80)).unwrap();
+        assert_eq!((h.host(), h.port()), ("fe80::1", 80));
+        let h = HostPort::parse("fe80::1", Some(80)).unwrap();
+        assert_eq!((h.host(), h.port()), ("fe80::1", 80));
+        assert!(HostPort::parse("[::1]", None).is_err());
+        assert!(HostPort::parse("[::1:8080", None).is_err());
+        assert!(HostPort::parse("[::1]8080", None).is_err());
+        assert!(HostPort::parse("[example.com]:80", None).is_err());
+        assert!(HostPort::parse("[1.2.3.4]:80", None).is_err());
+        assert_eq!(HostPort::new("[::1]", 22).unwrap().host(), "::1");
+    }
+
+    #[test]
+    fn invalid_ports() {
+        for s in [
+            "example.com:0",
+            "[::1]:0",
+            "example.com:",
+            "example.com:65536",
+            "example.com:-1",
+            "example.com:http",
+        ] {
+            assert!(HostPort::parse(s, Some(80)).is_err(), "{:?}", s);
+        }
+        assert!(HostPort::new("example.com", 0).is_err());
+        assert!(HostPort::parse("bad_host:80", None).is_err());
+    }
+
+    #[test]
+    fn host_port_round_trip() {
+        for s in [
+            "example.com:8080",
+            "192.168.1.10:22",
+            "[::1]:8080",
+            "[fe80::1]:443",
+        ] {
+            assert_eq!(s.parse::<HostPort>().unwrap().to_string(), s);
+        }
+    }
+}
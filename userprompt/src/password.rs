@@ -0,0 +1,487 @@
+//! Policies for new passwords, estimates of password strength, and hashed passwords.
+
+/// Passwords that are rejected by a policy that rejects common passwords.
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "password",
+    "12345678",
+    "qwerty",
+    "123456789",
+    "12345",
+    "1234",
+    "111111",
+    "1234567",
+    "dragon",
+    "123123",
+    "baseball",
+    "abc123",
+    "football",
+    "monkey",
+    "letmein",
+    "696969",
+    "shadow",
+    "master",
+    "666666",
+    "qwertyuiop",
+    "123321",
+    "mustang",
+    "1234567890",
+    "michael",
+    "654321",
+    "superman",
+    "1qaz2wsx",
+    "7777777",
+    "121212",
+    "000000",
+    "qazwsx",
+    "123qwe",
+    "killer",
+    "trustno1",
+    "jordan",
+    "jennifer",
+    "zxcvbnm",
+    "asdfgh",
+    "hunter",
+    "hunter2",
+    "buster",
+    "soccer",
+    "harley",
+    "batman",
+    "andrew",
+    "tigger",
+    "sunshine",
+    "iloveyou",
+    "2000",
+    "charlie",
+    "robert",
+    "thomas",
+    "hockey",
+    "ranger",
+    "daniel",
+    "starwars",
+    "klaster",
+    "112233",
+    "george",
+    "computer",
+    "michelle",
+    "jessica",
+    "pepper",
+    "1111",
+    "zxcvbn",
+    "555555",
+    "11111111",
+    "131313",
+    "freedom",
+    "777777",
+    "pass",
+    "maggie",
+    "159753",
+    "aaaaaa",
+    "ginger",
+    "princess",
+    "joshua",
+    "cheese",
+    "amanda",
+    "summer",
+    "love",
+    "ashley",
+    "nicole",
+    "chelsea",
+    "biteme",
+    "matthew",
+    "access",
+    "yankees",
+    "987654321",
+    "dallas",
+    "austin",
+    "thunder",
+    "taylor",
+    "matrix",
+    "welcome",
+    "welcome1",
+    "admin",
+    "administrator",
+    "changeme",
+    "passw0rd",
+    "password1",
+    "password123",
+    "p@ssw0rd",
+    "qwerty123",
+    "letmein1",
+    "secret",
+    "root",
+    "toor",
+];
+
+/// The requirements that a new password must meet.
+#[derive(Clone, Debug, Default)]
+pub struct PasswordPolicy {
+    /// The minimum number of characters
+    pub min_length: usize,
+    /// Require at least one lowercase letter
+    pub require_lowercase: bool,
+    /// Require at least one uppercase letter
+    pub require_uppercase: bool,
+    /// Require at least one digit
+    pub require_digit: bool,
+    /// Require at least one character that is not a letter or digit
+    pub require_symbol: bool,
+    /// Reject passwords from a list of commonly used passwords
+    pub reject_common: bool,
+    /// The minimum estimated entropy in bits
+    pub min_entropy: Option<f64>,
+}
+
+impl PasswordPolicy {
+    /// Check a password against the policy, returning the reasons that it is not acceptable.
+    pub fn check(&self, password: &str) -> Result<(), Vec<String>> {
+        let mut reasons = Vec::new();
+        if password.chars().count() < self.min_length {
+            reasons.push(format!(
+                "must be at least {} characters long",
+                self.min_length
+            ));
+        }
+        if self.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            reasons.push("must contain a lowercase letter".to_string());
+        }
+        if self.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            reasons.push("must contain an uppercase letter".to_string());
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            reasons.push("must contain a digit".to_string());
+        }
+        if self.require_symbol && !password.chars().any(|c| !c.is_alphanumeric()) {
+            reasons.push("must contain a symbol".to_string());
+        }
+        if self.reject_common && is_common(password) {
+            reasons.push("is a commonly used password".to_string());
+        }
+        if let Some(min) = self.min_entropy {
+            let e = entropy(password);
+            if e < min {
+                reasons.push(format!(
+                    "is too easy to guess ({:.0} bits, {:.0} required)",
+                    e, min
+                ));
+            }
+        }
+        if reasons.is_empty() {
+            Ok(())
+        } else {
+            Err(reasons)
+        }
+    }
+}
+
+/// Returns true if the password is on the list of commonly used passwords
+pub fn is_common(password: &str) -> bool {
+    let lower = password.to_lowercase();
+    COMMON_PASSWORDS.contains(&lower.as_str())
+}
+
+/// Estimate the entropy of a password in bits, from its length and the kinds of characters used.
+/// Common passwords have no entropy.
+pub fn entropy(password: &str) -> f64 {
+    if is_common(password) {
+        return 0.0;
+    }
+    let mut pool = 0;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if password
+        .chars()
+        .any(|c| c.is_ascii() && !c.is_ascii_alphanumeric())
+    {
+        pool += 33;
+    }
+    if !password.is_ascii() {
+        pool += 100;
+    }
+    if pool == 0 {
+        return 0.0;
+    }
+    password.chars().count() as f64 * (pool as f64).log2()
+}
+
+/// A rough rating of how hard a password is to guess
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Strength {
+    /// Under 28 bits of entropy
+    VeryWeak,
+    /// Under 36 bits of entropy
+    Weak,
+    /// Under 60 bits of entropy
+    Reasonable,
+    /// Under 128 bits of entropy
+    Strong,
+    /// 128 bits of entropy or more
+    VeryStrong,
+}
+
+impl Strength {
+    /// Rate a password
+    pub fn of(password: &str) -> Self {
+        let e = entropy(password);
+        if e < 28.0 {
+            Strength::VeryWeak
+        } else if e < 36.0 {
+            Strength::Weak
+        } else if e < 60.0 {
+            Strength::Reasonable
+        } else if e < 128.0 {
+            Strength::Strong
+        } else {
+            Strength::VeryStrong
+        }
+    }
+}
+
+impl std::fmt::Display for Strength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Strength::VeryWeak => "very weak",
+            Strength::Weak => "weak",
+            Strength::Reasonable => "reasonable",
+            Strength::Strong => "strong",
+            Strength::VeryStrong => "very strong",
+        })
+    }
+}
+
+/// Draw a bar showing the strength of a password
+#[cfg(feature = "egui")]
+pub fn strength_meter(ui: &mut egui::Ui, password: &str) {
+    let e = entropy(password);
+    let s = Strength::of(password);
+    let color = match s {
+        Strength::VeryWeak => egui::Color32::RED,
+        Strength::Weak => egui::Color32::from_rgb(255, 140, 0),
+        Strength::Reasonable => egui::Color32::YELLOW,
+        Strength::Strong => egui::Color32::LIGHT_GREEN,
+        Strength::VeryStrong => egui::Color32::GREEN,
+    };
+    let bar = egui::ProgressBar::new((e / 128.0).min(1.0) as f32)
+        .fill(color)
+        .text(format!("Strength: {} ({:.0} bits)", s, e));
+    ui.add(bar);
+}
+
+/// A new password that is collected and confirmed like [`crate::Password2`], but only the hash of the password is kept.
+/// The hash is an argon2 hash in the PHC string format, and is what gets serialized.
+/// Deserializing a string that is not a PHC string fails, an empty string is a hash with no password set.
+#[cfg(feature = "hash")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct PasswordHash(String);
+
+#[cfg(feature = "hash")]
+impl PasswordHash {
+    /// Hash a password with a new random salt
+    pub fn new(password: &str) -> Self {
+        use password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = argon2::Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("hashing a password with the default parameters");
+        PasswordHash(hash.to_string())
+    }
+
+    /// Use an existing hash in the PHC string format, returning None if it cannot be parsed
+    pub fn from_phc(phc: &str) -> Option<Self> {
+        password_hash::PasswordHash::new(phc)
+            .ok()
+            .map(|_| PasswordHash(phc.to_string()))
+    }
+
+    /// Get the hash in the PHC string format, empty if no password has been entered
+    pub fn phc(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if the password matches the hash
+    pub fn verify(&self, password: &str) -> bool {
+        use password_hash::PasswordVerifier;
+        match password_hash::PasswordHash::new(&self.0) {
+            Ok(h) => argon2::Argon2::default()
+                .verify_password(password.as_bytes(), &h)
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(feature = "hash")]
+impl From<PasswordHash> for String {
+    fn from(value: PasswordHash) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "hash")]
+impl std::convert::TryFrom<String> for PasswordHash {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Ok(PasswordHash::default());
+        }
+        PasswordHash::from_phc(&value)
+            .ok_or_else(|| "not a password hash in the PHC string format".to_string())
+    }
+}
+
+#[cfg(feature = "hash")]
+impl std::fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "hash")]
+impl crate::Prompting for PasswordHash {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, crate::Error> {
+        Self::prompt_with(name, comment, &crate::PromptOptions::default())
+    }
+
+    fn prompt_with(
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<Self, crate::Error> {
+        let p = crate::Password2::prompt_with(name, comment, options)?;
+        Ok(PasswordHash::new(p.expose_secret()))
+    }
+}
+
+/// The password being entered is kept in the egui memory rather than in the object. It is hashed once the password
+/// fields lose focus with an acceptable password, and is then removed from the egui memory and erased.
+/// Changing the password clears the hash until the new password has been hashed.
+#[cfg(all(feature = "hash", feature = "egui"))]
+impl crate::EguiPrompting for PasswordHash {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_gui_with(ui, name, comment, &crate::PromptOptions::default())
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        if self.0.is_empty() {
+            return Err(format!("{} password is not set", name.unwrap_or("")));
+        }
+        Ok(())
+    }
+
+    fn build_gui_with(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        let id = ui.make_persistent_id(("hash", name));
+        // Taken out of the memory rather than copied, so that the only copy of the password is the one being edited
+        let mut entry: crate::Password2 = ui.data_mut(|d| d.remove_temp(id)).unwrap_or_default();
+        let (r, responses) = entry.edit_gui(ui, name, comment, options);
+        if responses.iter().any(|r| r.changed()) {
+            self.0.clear();
+        }
+        let entered = !entry.0.is_empty() || !entry.1.is_empty();
+        if entered && r.is_ok() && !responses.iter().any(|r| r.has_focus()) {
+            self.0 = PasswordHash::new(entry.expose_secret()).0;
+            entry = crate::Password2::default();
+        }
+        let unchanged = entry.0.is_empty() && entry.1.is_empty();
+        if !unchanged {
+            ui.data_mut(|d| d.insert_temp(id, entry));
+        }
+        if unchanged && !self.0.is_empty() {
+            ui.label("A password is set, enter a new one to change it");
+            return Ok(());
+        }
+        r.and_then(|_| self.check(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_accepts_a_good_password() {
+        let policy = PasswordPolicy {
+            min_length: 10,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            reject_common: true,
+            min_entropy: Some(50.0),
+        };
+        assert!(policy.check("Corr3ct-Horse-Battery").is_ok());
+    }
+
+    #[test]
+    fn policy_gives_every_reason() {
+        let policy = PasswordPolicy {
+            min_length: 12,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            reject_common: true,
+            ..Default::default()
+        };
+        let reasons = policy.check("password").unwrap_err();
+        assert_eq!(reasons.len(), 5);
+        assert!(reasons[0].contains("12 characters"));
+        assert_eq!(reasons[4], "is a commonly used password");
+    }
+
+    #[test]
+    fn policy_counts_characters_not_bytes() {
+        let policy = PasswordPolicy {
+            min_length: 4,
+            ..Default::default()
+        };
+        assert!(policy.check("äöü").is_err());
+        assert!(policy.check("äöüß").is_ok());
+    }
+
+    #[test]
+    fn policy_minimum_entropy() {
+        let policy = PasswordPolicy {
+            min_entropy: Some(40.0),
+            ..Default::default()
+        };
+        assert!(policy.check("abcdefgh").is_err());
+        assert!(policy.check("abcdefghij").is_ok());
+    }
+
+    #[test]
+    fn entropy_of_passwords() {
+        assert_eq!(entropy(""), 0.0);
+        assert_eq!(entropy("Password1"), 0.0);
+        assert!((entropy("abcd") - 4.0 * 26f64.log2()).abs() < 1e-9);
+        assert!((entropy("aB3!") - 4.0 * 95f64.log2()).abs() < 1e-9);
+        assert!(entropy("abcd") < entropy("abcde"));
+        assert!(entropy("abcd") < entropy("abc1"));
+    }
+
+    #[test]
+    fn strength_ratings() {
+        assert_eq!(Strength::of("secret"), Strength::VeryWeak);
+        assert_eq!(Strength::of("abcdefg"), Strength::Weak);
+        assert_eq!(Strength::of("aB3!aB3!aB3!"), Strength::Strong);
+    }
+}
@@ -0,0 +1,164 @@
+//! Expansion of the paths entered at prompts.
+//!
+//! A leading `~` is replaced with the home directory, and `$VAR` or `${VAR}` with the value of the
+//! environment variable. Variables that are not set are left as they were typed.
+
+use std::path::{Path, PathBuf};
+
+/// The home directory of the user
+fn home_dir() -> Option<String> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+}
+
+/// Expand a leading `~` and any environment variables in a path
+pub fn expand(typed: &str) -> String {
+    let mut s = String::new();
+    let mut rest = typed;
+    if let Some(r) = typed.strip_prefix('~') {
+        if r.is_empty() || r.starts_with(['/', '\\']) {
+            if let Some(home) = home_dir() {
+                s.push_str(&home);
+                rest = r;
+            }
+        }
+    }
+    while let Some(i) = rest.find('$') {
+        s.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let (var, len) = match after.strip_prefix('{') {
+            Some(b) => match b.find('}') {
+                Some(end) => (&b[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        match std::env::var(var) {
+            Ok(v) if !var.is_empty() => {
+                s.push_str(&v);
+                rest = &after[len..];
+            }
+            _ => {
+                s.push('$');
+                rest = after;
+            }
+        }
+    }
+    s.push_str(rest);
+    s
+}
+
+/// Canonicalize a path that may not exist yet, by canonicalizing the part of it that does exist
+fn canonicalize(pb: &Path) -> PathBuf {
+    if let Ok(c) = std::fs::canonicalize(pb) {
+        return c;
+    }
+    match (pb.parent(), pb.file_name()) {
+        (Some(p), Some(f)) if !p.as_os_str().is_empty() => canonicalize(p).join(f),
+        _ => pb.to_path_buf(),
+    }
+}
+
+/// Expand a path as typed by the user, putting a relative path in the base directory if there is one,
+/// and canonicalizing it if requested. Nothing entered gives an empty path.
+pub fn resolve(typed: &str, base: Option<&Path>, canonical: bool) -> PathBuf {
+    if typed.is_empty() {
+        return PathBuf::new();
+    }
+    let mut pb = PathBuf::from(expand(typed));
+    if pb.is_relative() {
+        if let Some(b) = base {
+            pb = b.join(pb);
+        }
+    }
+    if canonical {
+        if pb.is_relative() {
+            if let Ok(cwd) = std::env::current_dir() {
+                pb = cwd.join(pb);
+            }
+        }
+        pb = canonicalize(&pb);
+    }
+    pb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_home() {
+        let home = home_dir().unwrap();
+        assert_eq!(expand("~"), home);
+        assert_eq!(expand("~/a/b"), format!("{}/a/b", home));
+        assert_eq!(expand("~user/a"), "~user/a");
+        assert_eq!(expand("a/~/b"), "a/~/b");
+    }
+
+    #[test]
+    fn expand_variables() {
+        std::env::set_var("USERPROMPT_TEST_EXPAND", "/data");
+        std::env::remove_var("USERPROMPT_TEST_UNSET");
+        assert_eq!(expand("$USERPROMPT_TEST_EXPAND/x"), "/data/x");
+        assert_eq!(expand("${USERPROMPT_TEST_EXPAND}x"), "/datax");
+        assert_eq!(expand("a$USERPROMPT_TEST_EXPAND.b"), "a/data.b");
+        assert_eq!(
+            expand("$USERPROMPT_TEST_UNSET/x"),
+            "$USERPROMPT_TEST_UNSET/x"
+        );
+        assert_eq!(
+            expand("${USERPROMPT_TEST_UNSET}"),
+            "${USERPROMPT_TEST_UNSET}"
+        );
+        assert_eq!(
+            expand("${USERPROMPT_TEST_EXPAND"),
+            "${USERPROMPT_TEST_EXPAND"
+        );
+        assert_eq!(expand("cost $5 and $"), "cost $5 and $");
+        assert_eq!(expand("${}"), "${}");
+    }
+
+    #[test]
+    fn resolve_relative() {
+        let base = Path::new("/base");
+        assert_eq!(resolve("", Some(base), false), PathBuf::new());
+        assert_eq!(resolve("a.txt", None, false), PathBuf::from("a.txt"));
+        assert_eq!(
+            resolve("a.txt", Some(base), false),
+            PathBuf::from("/base/a.txt")
+        );
+        assert_eq!(
+            resolve("/abs/a.txt", Some(base), false),
+            PathBuf::from("/abs/a.txt")
+        );
+        let home = home_dir().unwrap();
+        assert_eq!(
+            resolve("~/a.txt", Some(base), false),
+            Path::new(&home).join("a.txt")
+        );
+    }
+
+    #[test]
+    fn resolve_canonical() {
+        let dir = std::env::temp_dir().join(format!("userprompt-resolve-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let canon = std::fs::canonicalize(&dir).unwrap();
+        assert_eq!(resolve("sub/../sub", Some(&dir), true), canon.join("sub"));
+        assert_eq!(
+            resolve("sub/new/file.txt", Some(&dir), true),
+            canon.join("sub").join("new").join("file.txt")
+        );
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(
+            resolve(".", None, true),
+            std::fs::canonicalize(cwd).unwrap()
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
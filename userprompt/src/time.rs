@@ -0,0 +1,330 @@
+//! Prompting for durations, and for dates and times with the chrono feature.
+//!
+//! Durations are entered as a number followed by a unit, such as `30s`, `5m` or `1h30m`.
+//! The units are `d`, `h`, `m`, `s`, `ms`, `us` and `ns`, and a number without a unit is in seconds,
+//! so `90` is a minute and a half and `1h30` is an hour and thirty seconds.
+
+use crate::{Error, Prompting};
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// The units accepted in a duration, with the number of nanoseconds in each
+const UNITS: &[(&str, u128)] = &[
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// Parse a duration such as `30s`, `5m`, `1h30m` or `1.5h`
+///
+/// A number without a unit at the end is in seconds.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    if let Ok(secs) = s.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+    let mut nanos: u128 = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let num_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (num, tail) = rest.split_at(num_end);
+        let num: f64 = num.parse().ok()?;
+        let tail = tail.trim_start();
+        let unit_end = tail
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+        let unit = match unit {
+            "" if tail.trim().is_empty() => "s",
+            unit => unit,
+        };
+        let (_, scale) = UNITS.iter().find(|(u, _)| *u == unit)?;
+        nanos = nanos.checked_add((num * *scale as f64).round() as u128)?;
+        rest = tail.trim_start();
+    }
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+/// Format a duration in the form accepted by parse_duration, such as `1h30m`
+pub fn format_duration(d: &Duration) -> String {
+    let mut nanos = d.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+    let mut s = String::new();
+    for (unit, scale) in UNITS {
+        let n = nanos / scale;
+        if n > 0 {
+            s.push_str(&format!("{}{}", n, unit));
+            nanos %= scale;
+        }
+    }
+    s
+}
+
+impl Prompting for Duration {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        crate::prompt_parsed(name, comment, |s| {
+            parse_duration(s).ok_or_else(|| {
+                "Invalid duration, enter a duration like 30s, 5m or 1h30m".to_string()
+            })
+        })
+    }
+}
+
+#[cfg(feature = "egui")]
+impl crate::EguiPrompting for Duration {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        crate::form::labels(ui, name, comment);
+        let total = self.as_secs();
+        let (mut h, mut m, mut s) = (total / 3600, (total / 60) % 60, total % 60);
+        let mut ms = self.subsec_millis();
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut h).suffix(" h"));
+            ui.add(
+                egui::DragValue::new(&mut m)
+                    .clamp_range(0..=59)
+                    .suffix(" m"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut s)
+                    .clamp_range(0..=59)
+                    .suffix(" s"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut ms)
+                    .clamp_range(0..=999)
+                    .suffix(" ms"),
+            );
+        });
+        *self = Duration::new(
+            h.saturating_mul(3600).saturating_add(m * 60 + s),
+            ms * 1_000_000 + self.subsec_nanos() % 1_000_000,
+        );
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Parse a time such as `13:45` or `13:45:30`
+#[cfg(feature = "chrono")]
+fn parse_time(s: &str) -> Option<chrono::NaiveTime> {
+    let s = s.trim();
+    chrono::NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| chrono::NaiveTime::parse_from_str(s, "%H:%M"))
+        .ok()
+}
+
+/// Parse a date and time such as `2024-05-01 13:45` or `2024-05-01T13:45:30`
+#[cfg(feature = "chrono")]
+fn parse_datetime(s: &str) -> Option<chrono::NaiveDateTime> {
+    let s = s.trim();
+    let (date, time) = s.split_once([' ', 'T'])?;
+    let date = date.parse::<chrono::NaiveDate>().ok()?;
+    Some(date.and_time(parse_time(time)?))
+}
+
+/// Prompt for a value with a parser, asking again with the example format when the input is not valid
+#[cfg(feature = "chrono")]
+fn prompt_example<T>(
+    name: Option<&str>,
+    comment: Option<&str>,
+    parse: impl Fn(&str) -> Option<T>,
+    example: &str,
+) -> Result<T, Error> {
+    crate::prompt_parsed(name, comment, |s| {
+        parse(s).ok_or_else(|| format!("Invalid input, enter a value like {}", example))
+    })
+}
+
+#[cfg(feature = "chrono")]
+impl Prompting for chrono::NaiveDate {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        prompt_example(name, comment, |s| s.parse().ok(), "2024-05-01")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Prompting for chrono::NaiveTime {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        prompt_example(name, comment, parse_time, "13:45 or 13:45:30")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Prompting for chrono::NaiveDateTime {
+    fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, Error> {
+        prompt_example(name, comment, parse_datetime, "2024-05-01 13:45")
+    }
+}
+
+/// Show the hour, minute and second of a time
+#[cfg(all(feature = "chrono", feature = "egui"))]
+fn time_edit(ui: &mut egui::Ui, t: &mut chrono::NaiveTime) {
+    use chrono::Timelike;
+    let (mut h, mut m, mut s) = (t.hour(), t.minute(), t.second());
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::DragValue::new(&mut h)
+                .clamp_range(0..=23)
+                .suffix(" h"),
+        );
+        ui.add(
+            egui::DragValue::new(&mut m)
+                .clamp_range(0..=59)
+                .suffix(" m"),
+        );
+        ui.add(
+            egui::DragValue::new(&mut s)
+                .clamp_range(0..=59)
+                .suffix(" s"),
+        );
+    });
+    if let Some(n) = chrono::NaiveTime::from_hms_opt(h, m, s) {
+        *t = n;
+    }
+}
+
+#[cfg(all(feature = "chrono", feature = "egui"))]
+impl crate::EguiPrompting for chrono::NaiveDate {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        crate::form::labels(ui, name, comment);
+        ui.add(egui_extras::DatePickerButton::new(self).id_source(name.unwrap_or("date")));
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "chrono", feature = "egui"))]
+impl crate::EguiPrompting for chrono::NaiveTime {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        crate::form::labels(ui, name, comment);
+        time_edit(ui, self);
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "chrono", feature = "egui"))]
+impl crate::EguiPrompting for chrono::NaiveDateTime {
+    fn build_gui(
+        &mut self,
+        ui: &mut egui::Ui,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        crate::form::labels(ui, name, comment);
+        let mut date = self.date();
+        let mut time = self.time();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui_extras::DatePickerButton::new(&mut date).id_source(name.unwrap_or("datetime")),
+            );
+            time_edit(ui, &mut time);
+        });
+        *self = date.and_time(time);
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_units() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1h 30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1.5h"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(172_800)));
+        assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_duration("3us"), Some(Duration::from_micros(3)));
+        assert_eq!(parse_duration("7ns"), Some(Duration::from_nanos(7)));
+    }
+
+    #[test]
+    fn parse_bare_seconds() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration(" 0.5 "), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1h30"), Some(Duration::from_secs(3630)));
+        assert_eq!(parse_duration("1m 5"), Some(Duration::from_secs(65)));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("   "), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("5 10m"), None);
+        assert_eq!(parse_duration("-5s"), None);
+        assert_eq!(parse_duration("-5"), None);
+        assert_eq!(parse_duration("1..5s"), None);
+    }
+
+    #[test]
+    fn format_units() {
+        assert_eq!(format_duration(&Duration::ZERO), "0s");
+        assert_eq!(format_duration(&Duration::from_secs(5400)), "1h30m");
+        assert_eq!(format_duration(&Duration::from_secs(90_061)), "1d1h1m1s");
+        assert_eq!(format_duration(&Duration::from_millis(1500)), "1s500ms");
+        assert_eq!(format_duration(&Duration::new(0, 1_001)), "1us1ns");
+    }
+
+    #[test]
+    fn round_trip() {
+        for d in [
+            Duration::ZERO,
+            Duration::from_nanos(1),
+            Duration::from_millis(999),
+            Duration::from_secs(59),
+            Duration::from_secs(3630),
+            Duration::new(86_400 * 400 + 3_723, 4_005_006),
+        ] {
+            assert_eq!(parse_duration(&format_duration(&d)), Some(d));
+        }
+        for s in ["1h30m", "2d3h", "1s500ms", "1us1ns"] {
+            assert_eq!(format_duration(&parse_duration(s).unwrap()), s);
+        }
+    }
+}
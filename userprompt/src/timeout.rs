@@ -0,0 +1,380 @@
+//! Timeouts for prompts on the command line, so that unattended runs do not wait forever for input.
+//!
+//! A session timeout set with [`set_timeout`] limits how long each line of input is waited for,
+//! after which the prompt returns [`Error::Timeout`]. A field timeout, given with
+//! [`prompt_or_default`] or the `#[prompt(timeout_secs = 30)]` attribute of the derive macro,
+//! limits the time for the whole field, after which the field takes its default value.
+//!
+//! While a timeout is in effect, input from a terminal is read with the terminal in raw mode, so
+//! that a prompt that times out does not leave a read waiting on the terminal. Input that is not
+//! from a terminal is read by a background thread one line at a time as prompts ask for it, and a
+//! line that arrives after its prompt has timed out is dropped.
+
+use crate::{Error, Prompting};
+use std::cell::Cell;
+use std::io::IsTerminal;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// The timeout applied to every line of input, None to wait forever
+static SESSION_TIMEOUT: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// The background thread reading standard input when it is not a terminal
+static PIPE: OnceLock<Mutex<PipeReader>> = OnceLock::new();
+
+/// Reads lines from standard input on a background thread, only when a prompt asks for one
+struct PipeReader {
+    /// Asks the thread to read a line
+    requests: Sender<()>,
+    /// The lines read by the thread
+    lines: Receiver<std::io::Result<String>>,
+    /// True when a line has been asked for by a prompt that timed out before it arrived
+    stale: bool,
+}
+
+thread_local! {
+    /// The time by which the field currently being prompted must be entered
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Set the timeout applied to each line of input for every prompt, None to wait forever.
+pub fn set_timeout(timeout: Option<Duration>) {
+    *SESSION_TIMEOUT.lock().unwrap() = timeout;
+}
+
+/// Get the timeout applied to each line of input for every prompt.
+pub fn timeout() -> Option<Duration> {
+    *SESSION_TIMEOUT.lock().unwrap()
+}
+
+/// Run a prompt, returning [`Error::Timeout`] if it is not entered within the given time.
+pub fn with_timeout<T>(
+    timeout: Duration,
+    f: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, Error> {
+    let deadline = Instant::now() + timeout;
+    let previous = DEADLINE.with(|d| d.get());
+    let deadline = previous.map_or(deadline, |p| p.min(deadline));
+    DEADLINE.with(|d| d.set(Some(deadline)));
+    let _restore = RestoreDeadline(previous);
+    f()
+}
+
+/// Puts back the deadline of the enclosing prompt when dropped, even if the prompt panics
+struct RestoreDeadline(Option<Instant>);
+
+impl Drop for RestoreDeadline {
+    fn drop(&mut self) {
+        DEADLINE.with(|d| d.set(self.0));
+    }
+}
+
+/// Run a prompt, giving the default value if it is not entered within the given time.
+pub fn or_default<T: Default>(
+    timeout: Duration,
+    f: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, Error> {
+    match with_timeout(timeout, f) {
+        Err(Error::Timeout) => {
+            println!();
+            println!("No input received, using the default value");
+            Ok(T::default())
+        }
+        r => r,
+    }
+}
+
+/// Prompt for a value, returning [`Error::Timeout`] if it is not entered within the given time.
+pub fn prompt_with_timeout<T: Prompting>(
+    timeout: Duration,
+    name: Option<&str>,
+    comment: Option<&str>,
+) -> Result<T, Error> {
+    with_timeout(timeout, || T::prompt(name, comment))
+}
+
+/// Prompt for a value, giving the default value if it is not entered within the given time.
+pub fn prompt_or_default<T: Prompting + Default>(
+    timeout: Duration,
+    name: Option<&str>,
+    comment: Option<&str>,
+) -> Result<T, Error> {
+    or_default(timeout, || T::prompt(name, comment))
+}
+
+/// The time by which the current line of input must be entered, None to wait forever
+fn deadline() -> Option<Instant> {
+    let session = timeout().map(|t| Instant::now() + t);
+    match (DEADLINE.with(|d| d.get()), session) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// The time left until a deadline, None to wait forever
+fn until(deadline: Option<Instant>) -> Option<Duration> {
+    deadline.map(|d| d.saturating_duration_since(Instant::now()))
+}
+
+/// Wait for a line from the receiver for the given amount of time, or forever
+fn receive(r: &Receiver<std::io::Result<String>>, wait: Option<Duration>) -> Result<String, Error> {
+    let line = match wait {
+        Some(wait) => r.recv_timeout(wait).map_err(|e| match e {
+            RecvTimeoutError::Timeout => Error::Timeout,
+            RecvTimeoutError::Disconnected => {
+                Error::InputError(std::io::ErrorKind::UnexpectedEof.into())
+            }
+        })?,
+        None => r
+            .recv()
+            .map_err(|_| Error::InputError(std::io::ErrorKind::UnexpectedEof.into()))?,
+    };
+    line.map_err(Error::InputError)
+}
+
+impl PipeReader {
+    /// Start the thread, which reads a line from standard input for each request
+    fn start() -> Self {
+        let (requests, r) = std::sync::mpsc::channel::<()>();
+        let (s, lines) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            while r.recv().is_ok() {
+                let mut buffer = String::new();
+                let line = std::io::stdin().read_line(&mut buffer).map(|_| buffer);
+                let done = !matches!(&line, Ok(l) if !l.is_empty());
+                if s.send(line).is_err() || done {
+                    break;
+                }
+            }
+        });
+        Self {
+            requests,
+            lines,
+            stale: false,
+        }
+    }
+
+    /// Read a line before the deadline, first dropping a line asked for by a prompt that timed out
+    fn read_line(&mut self, deadline: Option<Instant>) -> Result<String, Error> {
+        if self.stale {
+            receive(&self.lines, until(deadline))?;
+            self.stale = false;
+        }
+        self.requests
+            .send(())
+            .map_err(|_| Error::InputError(std::io::ErrorKind::UnexpectedEof.into()))?;
+        let line = receive(&self.lines, until(deadline));
+        self.stale = matches!(line, Err(Error::Timeout));
+        line
+    }
+}
+
+/// Read a line from standard input, including the line ending, honoring any timeout in effect.
+pub(crate) fn read_line() -> Result<String, Error> {
+    let deadline = deadline();
+    if deadline.is_none() && PIPE.get().is_none() {
+        let mut buffer = String::new();
+        std::io::stdin()
+            .read_line(&mut buffer)
+            .map_err(Error::InputError)?;
+        return Ok(buffer);
+    }
+    if std::io::stdin().is_terminal() {
+        if let Some(line) = read_terminal(Echo::Plain, deadline) {
+            return line.map(|mut l| {
+                l.push('\n');
+                l
+            });
+        }
+    }
+    PIPE.get_or_init(|| Mutex::new(PipeReader::start()))
+        .lock()
+        .unwrap()
+        .read_line(deadline)
+}
+
+/// Read a password without showing it on screen, honoring any timeout in effect.
+/// Without a terminal the password is read as a line of standard input.
+pub(crate) fn read_secret() -> Result<String, Error> {
+    let deadline = deadline();
+    if deadline.is_none() {
+        return rpassword::read_password().map_err(Error::InputError);
+    }
+    match read_terminal(Echo::Hidden, deadline) {
+        Some(r) => r,
+        None => read_line().map(|l| l.trim_end_matches(['\r', '\n']).to_string()),
+    }
+}
+
+/// Read a password, showing the mask character for each character typed, honoring any timeout in effect.
+/// Falls back to reading without any echo when the terminal cannot be put in raw mode.
+pub(crate) fn read_masked(mask: char) -> Result<String, Error> {
+    match read_terminal(Echo::Mask(mask), deadline()) {
+        Some(r) => r,
+        None => read_secret(),
+    }
+}
+
+/// What is shown for each character typed into the terminal
+#[derive(Clone, Copy)]
+enum Echo {
+    /// The character itself
+    Plain,
+    /// The mask character
+    Mask(char),
+    /// Nothing
+    Hidden,
+}
+
+/// Read a line from the terminal in raw mode before the deadline, without the line ending.
+/// The terminal is always taken out of raw mode before returning, so a prompt that times out or
+/// is cancelled leaves it as it was. Returns None when the terminal cannot be put in raw mode.
+fn read_terminal(echo: Echo, deadline: Option<Instant>) -> Option<Result<String, Error>> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use std::io::Write;
+    crossterm::terminal::enable_raw_mode().ok()?;
+    // Reserve space up front so that typing does not leave copies of a password behind in reallocated memory
+    let mut buffer = zeroize::Zeroizing::new(String::with_capacity(256));
+    let mut stdout = std::io::stdout();
+    let erase = match echo {
+        Echo::Hidden => "",
+        _ => "\x08 \x08",
+    };
+    let r = loop {
+        if let Some(wait) = until(deadline) {
+            match event::poll(wait) {
+                Ok(true) => {}
+                Ok(false) => break Err(Error::Timeout),
+                Err(e) => break Err(Error::InputError(e)),
+            }
+        }
+        let k = match event::read() {
+            Ok(Event::Key(k)) if k.kind != KeyEventKind::Release => k,
+            Ok(_) => continue,
+            Err(e) => break Err(Error::InputError(e)),
+        };
+        match k.code {
+            KeyCode::Enter => break Ok(()),
+            KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Err(Error::Cancelled)
+            }
+            KeyCode::Backspace if buffer.pop().is_some() => print!("{}", erase),
+            KeyCode::Char(c) => {
+                buffer.push(c);
+                match echo {
+                    Echo::Plain => print!("{}", c),
+                    Echo::Mask(mask) => print!("{}", mask),
+                    Echo::Hidden => {}
+                }
+            }
+            _ => {}
+        }
+        let _ = stdout.flush();
+    };
+    let _ = crossterm::terminal::disable_raw_mode();
+    println!();
+    Some(r.map(|_| std::mem::take(&mut *buffer)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inner_deadline_is_capped_by_outer() {
+        let start = Instant::now();
+        let inner = with_timeout(Duration::from_secs(10), || {
+            with_timeout(Duration::from_secs(3600), || Ok(deadline()))
+        })
+        .unwrap()
+        .unwrap();
+        assert!(inner <= start + Duration::from_secs(11));
+        assert!(until(Some(inner)).unwrap() <= Duration::from_secs(10));
+        assert_eq!(DEADLINE.with(|d| d.get()), None);
+    }
+
+    #[test]
+    fn shorter_inner_deadline_is_kept() {
+        let (outer, inner) = with_timeout(Duration::from_secs(3600), || {
+            let outer = deadline();
+            let inner = with_timeout(Duration::from_secs(5), || Ok(deadline()))?;
+            Ok((outer, inner))
+        })
+        .unwrap();
+        assert!(inner.unwrap() < outer.unwrap());
+    }
+
+    #[test]
+    fn deadline_is_restored_after_panic() {
+        let r = std::panic::catch_unwind(|| {
+            with_timeout::<()>(Duration::from_secs(5), || panic!("prompt failed"))
+        });
+        assert!(r.is_err());
+        assert_eq!(DEADLINE.with(|d| d.get()), None);
+    }
+
+    #[test]
+    fn until_past_deadline_is_zero() {
+        assert_eq!(until(None), None);
+        let past = Instant::now() - Duration::from_secs(1);
+        assert_eq!(until(Some(past)), Some(Duration::ZERO));
+    }
+
+    /// A reader fed from the returned sender instead of standard input
+    fn pipe() -> (PipeReader, Sender<std::io::Result<String>>, Receiver<()>) {
+        let (requests, asked) = std::sync::mpsc::channel();
+        let (lines, r) = std::sync::mpsc::channel();
+        let reader = PipeReader {
+            requests,
+            lines: r,
+            stale: false,
+        };
+        (reader, lines, asked)
+    }
+
+    #[test]
+    fn pipe_reads_lines_in_order() {
+        let (mut reader, lines, asked) = pipe();
+        lines.send(Ok("first\n".to_string())).unwrap();
+        lines.send(Ok("second\n".to_string())).unwrap();
+        assert_eq!(reader.read_line(None).unwrap(), "first\n");
+        assert_eq!(reader.read_line(None).unwrap(), "second\n");
+        assert_eq!(asked.try_iter().count(), 2);
+    }
+
+    #[test]
+    fn pipe_drops_line_of_timed_out_prompt() {
+        let (mut reader, lines, asked) = pipe();
+        let r = reader.read_line(Some(Instant::now()));
+        assert!(matches!(r, Err(Error::Timeout)));
+        assert!(reader.stale);
+        lines.send(Ok("late\n".to_string())).unwrap();
+        lines.send(Ok("next\n".to_string())).unwrap();
+        assert_eq!(reader.read_line(None).unwrap(), "next\n");
+        assert!(!reader.stale);
+        assert_eq!(asked.try_iter().count(), 2);
+    }
+
+    #[test]
+    fn pipe_stays_stale_when_late_line_has_not_arrived() {
+        let (mut reader, lines, _asked) = pipe();
+        reader.stale = true;
+        let r = reader.read_line(Some(Instant::now()));
+        assert!(matches!(r, Err(Error::Timeout)));
+        assert!(reader.stale);
+        lines.send(Ok("late\n".to_string())).unwrap();
+        lines.send(Ok("next\n".to_string())).unwrap();
+        assert_eq!(reader.read_line(None).unwrap(), "next\n");
+    }
+
+    #[test]
+    fn pipe_end_of_input() {
+        let (mut reader, lines, _asked) = pipe();
+        drop(lines);
+        let r = reader.read_line(None);
+        assert!(
+            matches!(r, Err(Error::InputError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+    }
+}
@@ -0,0 +1,1154 @@
+//! A backend that presents a form to the user as a full screen terminal interface.
+//!
+//! The form is rebuilt every time a key is pressed, in the same immediate mode style as the egui
+//! backend. Tab and the arrow keys move between elements, enter or space activates the selected
+//! element, escape cancels the form and ctrl+s submits it.
+
+pub use userprompt_derive::TuiPrompting;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::collections::HashMap;
+
+/// The trait involved in building a terminal form for a type
+pub trait TuiPrompting: Sized {
+    /// Build the form elements for the object, applying any input from the user
+    /// # Arguments
+    /// * form - The form being built
+    /// * name - The optional name to display for the object
+    /// * comment - The optional comment to show to the user to explain the field being entered
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String>;
+
+    /// Perform any additional checks required in order to check the object for validness
+    fn check(&self, name: Option<&str>) -> Result<(), String>;
+
+    /// Build the form elements for the object, using options usually given by the prompt attribute
+    /// of the derive macro. Types that have no options build the same form as build_tui.
+    fn build_tui_with(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+        _options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        self.build_tui(form, name, comment)
+    }
+
+    /// Check the object, using options usually given by the prompt attribute of the derive macro.
+    fn check_with(
+        &self,
+        name: Option<&str>,
+        _options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        self.check(name)
+    }
+}
+
+/// The terminal form being built, along with the state kept between key presses
+#[derive(Default)]
+pub struct TuiForm {
+    /// The index of the element that has focus
+    focus: usize,
+    /// The number of elements that can have focus, counted while building
+    count: usize,
+    /// The key pressed by the user, taken by the element that uses it
+    event: Option<KeyEvent>,
+    /// The key of the open selection list and the highlighted option
+    open: Option<(String, usize)>,
+    /// Text entered for elements that parse their value, keyed by element
+    buffers: HashMap<String, String>,
+    /// The lines of the form
+    lines: Vec<Line<'static>>,
+    /// The line containing the element with focus
+    focus_line: usize,
+    /// How deeply nested the current element is
+    depth: usize,
+}
+
+impl TuiForm {
+    /// Construct a new form
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The key used to keep track of the state of an object with the given name
+    pub fn key(name: Option<&str>) -> String {
+        name.unwrap_or("value").to_string()
+    }
+
+    /// Register an element that can have focus, returns true when it has focus
+    fn focusable(&mut self) -> bool {
+        let focused = self.count == self.focus;
+        if focused {
+            self.focus_line = self.lines.len();
+        }
+        self.count += 1;
+        focused
+    }
+
+    /// Take the key pressed by the user if the element has focus
+    fn key_for(&mut self, focused: bool) -> Option<KeyEvent> {
+        if focused {
+            self.event
+        } else {
+            None
+        }
+    }
+
+    /// Mark the key pressed by the user as used
+    fn consume(&mut self) {
+        self.event = None;
+    }
+
+    /// Add a line to the form at the current depth
+    fn line(&mut self, focused: bool, mut spans: Vec<Span<'static>>) {
+        let marker = if focused { "> " } else { "  " };
+        spans.insert(
+            0,
+            Span::raw(format!("{}{}", "  ".repeat(self.depth), marker)),
+        );
+        let mut line = Line::from(spans);
+        if focused {
+            line = line.style(Style::default().add_modifier(Modifier::BOLD));
+        }
+        self.lines.push(line);
+    }
+
+    /// Add the optional comment for an element
+    fn comment(&mut self, comment: Option<&str>) {
+        if let Some(comment) = comment {
+            let s = Span::styled(comment.to_string(), Style::default().fg(Color::DarkGray));
+            self.line(false, vec![s]);
+        }
+    }
+
+    /// Add some plain text
+    pub fn text(&mut self, text: &str) {
+        self.line(false, vec![Span::raw(text.to_string())]);
+    }
+
+    /// Add an error message
+    pub fn error(&mut self, text: &str) {
+        let s = Span::styled(text.to_string(), Style::default().fg(Color::Red));
+        self.line(false, vec![s]);
+    }
+
+    /// Start a group of elements
+    pub fn begin_group(&mut self, name: Option<&str>, comment: Option<&str>) {
+        if let Some(n) = name {
+            let s = Span::styled(
+                format!("[{}]", n),
+                Style::default().add_modifier(Modifier::UNDERLINED),
+            );
+            self.line(false, vec![s]);
+        }
+        self.comment(comment);
+        self.depth += 1;
+    }
+
+    /// End a group of elements
+    pub fn end_group(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Add a text entry element, returns true when the text was changed
+    pub fn text_edit(
+        &mut self,
+        name: Option<&str>,
+        comment: Option<&str>,
+        value: &mut String,
+        secret: bool,
+    ) -> bool {
+        self.comment(comment);
+        let focused = self.focusable();
+        let mut changed = false;
+        if let Some(k) = self.key_for(focused) {
+            match k.code {
+                KeyCode::Char(c) if !k.modifiers.contains(KeyModifiers::CONTROL) => {
+                    value.push(c);
+                    changed = true;
+                    self.consume();
+                }
+                KeyCode::Backspace => {
+                    value.pop();
+                    changed = true;
+                    self.consume();
+                }
+                _ => {}
+            }
+        }
+        let shown = if secret {
+            "*".repeat(value.chars().count())
+        } else {
+            value.clone()
+        };
+        let cursor = if focused { "_" } else { "" };
+        self.line(
+            focused,
+            vec![
+                Span::raw(format!("{}: ", name.unwrap_or("Item"))),
+                Span::styled(
+                    format!("{}{}", shown, cursor),
+                    Style::default().add_modifier(Modifier::UNDERLINED),
+                ),
+            ],
+        );
+        changed
+    }
+
+    /// Add a text entry element for a value that is parsed from text
+    pub fn parsed<T>(
+        &mut self,
+        name: Option<&str>,
+        comment: Option<&str>,
+        value: &mut T,
+    ) -> Result<(), String>
+    where
+        T: std::str::FromStr + std::fmt::Display,
+    {
+        let key = Self::key(name);
+        let mut s = self
+            .buffers
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| value.to_string());
+        let focus = self.count == self.focus;
+        if self.text_edit(name, comment, &mut s, false) {
+            if let Ok(v) = s.trim().parse() {
+                *value = v;
+            }
+            self.buffers.insert(key.clone(), s.clone());
+        }
+        match s.trim().parse::<T>() {
+            Ok(_) => {
+                if !focus {
+                    self.buffers.remove(&key);
+                }
+                Ok(())
+            }
+            Err(_) => {
+                let e = format!("{} is not a valid value", s);
+                self.error(&e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Add a checkbox element, returns true when it was changed
+    pub fn checkbox(
+        &mut self,
+        name: Option<&str>,
+        comment: Option<&str>,
+        checked: &mut bool,
+    ) -> bool {
+        self.comment(comment);
+        let focused = self.focusable();
+        let mut changed = false;
+        if let Some(k) = self.key_for(focused) {
+            if matches!(k.code, KeyCode::Char(' ') | KeyCode::Enter) {
+                *checked = !*checked;
+                changed = true;
+                self.consume();
+            }
+        }
+        let b = if *checked { "[x] " } else { "[ ] " };
+        self.line(
+            focused,
+            vec![
+                Span::raw(b.to_string()),
+                Span::raw(name.unwrap_or("Item").to_string()),
+            ],
+        );
+        changed
+    }
+
+    /// Add a selection element, returns the new selection when the user changes it
+    pub fn choice(
+        &mut self,
+        key: &str,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &[&str],
+        current: &str,
+    ) -> Option<String> {
+        self.comment(comment);
+        let focused = self.focusable();
+        let index = options.iter().position(|o| *o == current).unwrap_or(0);
+        let mut selected = None;
+        let is_open = matches!(&self.open, Some((k, _)) if k == key);
+        if is_open && !focused {
+            self.open = None;
+        }
+        if let Some(k) = self.key_for(focused) {
+            if let Some((_, hl)) = self.open.as_mut().filter(|(k, _)| k == key) {
+                match k.code {
+                    KeyCode::Up | KeyCode::BackTab => *hl = hl.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Tab => {
+                        *hl = (*hl + 1).min(options.len().saturating_sub(1))
+                    }
+                    KeyCode::Enter | KeyCode::Char(' ') => {
+                        let o = options.get(*hl).map(|o| o.to_string());
+                        if o.as_deref() != Some(current) {
+                            selected = o;
+                        }
+                        self.open = None;
+                    }
+                    KeyCode::Esc => self.open = None,
+                    _ => {}
+                }
+                self.consume();
+            } else if matches!(k.code, KeyCode::Enter | KeyCode::Char(' ')) {
+                self.open = Some((key.to_string(), index));
+                self.consume();
+            }
+        }
+        let shown = selected.clone().unwrap_or_else(|| current.to_string());
+        self.line(
+            focused,
+            vec![
+                Span::raw(format!("{}: ", name.unwrap_or("Select"))),
+                Span::styled(
+                    format!("< {} >", shown),
+                    Style::default().add_modifier(Modifier::UNDERLINED),
+                ),
+            ],
+        );
+        if let Some((_, hl)) = self.open.clone().filter(|(k, _)| k == key) {
+            for (i, o) in options.iter().enumerate() {
+                let style = if i == hl {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                self.line(false, vec![Span::styled(format!("    {}", o), style)]);
+            }
+        }
+        selected
+    }
+
+    /// Add a button, returns true when it was pressed
+    pub fn button(&mut self, text: &str) -> bool {
+        let focused = self.focusable();
+        let mut pressed = false;
+        if let Some(k) = self.key_for(focused) {
+            if matches!(k.code, KeyCode::Enter | KeyCode::Char(' ')) {
+                pressed = true;
+                self.consume();
+            }
+        }
+        let style = if focused {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        self.line(focused, vec![Span::styled(format!("[ {} ]", text), style)]);
+        pressed
+    }
+
+    /// Prepare the form for building with the given key press
+    fn begin(&mut self, event: Option<KeyEvent>) {
+        self.event = event;
+        self.count = 0;
+        self.depth = 0;
+        self.focus_line = 0;
+        self.lines.clear();
+    }
+
+    /// Handle the key press when no element used it, returns the action requested by the user
+    fn navigate(&mut self) -> Option<Action> {
+        let k = self.event.take()?;
+        match k.code {
+            KeyCode::Tab | KeyCode::Down | KeyCode::Enter => self.focus += 1,
+            KeyCode::BackTab | KeyCode::Up => self.focus = self.focus.saturating_sub(1),
+            KeyCode::Esc => return Some(Action::Cancel),
+            KeyCode::Char('s') if k.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(Action::Submit)
+            }
+            KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(Action::Cancel)
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+impl TuiForm {
+    /// Build the whole form for a value with the given key press, followed by the submit and cancel buttons.
+    /// Returns the result of building the value and the action requested by the user.
+    fn build<T: TuiPrompting>(
+        &mut self,
+        value: &mut T,
+        name: Option<&str>,
+        comment: Option<&str>,
+        event: Option<KeyEvent>,
+    ) -> (Result<(), String>, Option<Action>) {
+        self.begin(event);
+        let r = value.build_tui(self, name, comment);
+        let submit = self.button("Submit");
+        let cancel = self.button("Cancel");
+        let mut action = self.navigate();
+        if submit {
+            action = Some(Action::Submit);
+        }
+        if cancel {
+            action = Some(Action::Cancel);
+        }
+        self.focus = self.focus.min(self.count.saturating_sub(1));
+        (r, action)
+    }
+}
+
+/// The actions that end the form
+#[derive(Debug, PartialEq)]
+enum Action {
+    /// The user wants to submit the form
+    Submit,
+    /// The user wants to abandon the form
+    Cancel,
+}
+
+/// Show a form for a default value of the type, taking over the terminal until the user submits or cancels the form
+pub fn prompt<T>(name: Option<&str>, comment: Option<&str>) -> Result<T, crate::Error>
+where
+    T: TuiPrompting + Default,
+{
+    prompt_value(T::default(), name, comment)
+}
+
+/// Show a form for the given value, taking over the terminal until the user submits or cancels the form
+pub fn prompt_value<T>(
+    mut value: T,
+    name: Option<&str>,
+    comment: Option<&str>,
+) -> Result<T, crate::Error>
+where
+    T: TuiPrompting,
+{
+    let mut terminal = ratatui::try_init().map_err(crate::Error::InputError)?;
+    let r = run(&mut terminal, &mut value, name, comment);
+    ratatui::restore();
+    r.map(|_| value)
+}
+
+/// Run the form until the user submits or cancels it
+fn run<T: TuiPrompting>(
+    terminal: &mut ratatui::DefaultTerminal,
+    value: &mut T,
+    name: Option<&str>,
+    comment: Option<&str>,
+) -> Result<(), crate::Error> {
+    let mut form = TuiForm::new();
+    let mut status: Option<String> = None;
+    let mut event = None;
+    loop {
+        for pass in 0..2 {
+            // The first pass applies the key press, the second shows the result of it
+            let event = if pass == 0 { event.take() } else { None };
+            let (r, action) = form.build(value, name, comment, event);
+            match action {
+                Some(Action::Submit) => match r.and(value.check(name)) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => status = Some(e),
+                },
+                Some(Action::Cancel) => return Err(crate::Error::Cancelled),
+                None => {}
+            }
+        }
+        if let Some(s) = &status {
+            form.error(s);
+        }
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                let height = area.height.saturating_sub(2) as usize;
+                let scroll = (form.focus_line + 1).saturating_sub(height);
+                let help = Line::styled(
+                    "tab/arrows: move  enter/space: select  ctrl+s: submit  esc: cancel",
+                    Style::default().fg(Color::DarkGray),
+                );
+                let p = ratatui::widgets::Paragraph::new(form.lines.clone())
+                    .scroll((scroll as u16, 0))
+                    .block(
+                        ratatui::widgets::Block::bordered()
+                            .title(name.unwrap_or("Form").to_string())
+                            .title_bottom(help),
+                    );
+                f.render_widget(p, area);
+            })
+            .map_err(crate::Error::InputError)?;
+        // Any other event, such as the terminal being resized, draws the form again
+        while let Event::Key(k) = event::read().map_err(crate::Error::InputError)? {
+            if k.kind == KeyEventKind::Press {
+                event = Some(k);
+                break;
+            }
+        }
+    }
+}
+
+macro_rules! tui_parsed {
+    ($($t:ty),*) => {
+        $(
+            impl TuiPrompting for $t {
+                fn build_tui(
+                    &mut self,
+                    form: &mut TuiForm,
+                    name: Option<&str>,
+                    comment: Option<&str>,
+                ) -> Result<(), String> {
+                    form.parsed(name, comment, self)
+                }
+
+                fn check(&self, _name: Option<&str>) -> Result<(), String> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+tui_parsed!(
+    u8,
+    i8,
+    u16,
+    i16,
+    u32,
+    i32,
+    u64,
+    i64,
+    usize,
+    isize,
+    f32,
+    f64,
+    u128,
+    i128,
+    char,
+    std::num::NonZeroU8,
+    std::num::NonZeroI8,
+    std::num::NonZeroU16,
+    std::num::NonZeroI16,
+    std::num::NonZeroU32,
+    std::num::NonZeroI32,
+    std::num::NonZeroU64,
+    std::num::NonZeroI64,
+    std::num::NonZeroU128,
+    std::num::NonZeroI128,
+    std::num::NonZeroUsize,
+    std::num::NonZeroIsize,
+    std::net::IpAddr,
+    std::net::Ipv4Addr,
+    std::net::Ipv6Addr,
+    std::net::SocketAddr,
+    crate::Hostname,
+    crate::HostPort
+);
+
+impl TuiPrompting for String {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        form.text_edit(name, comment, self, false);
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl TuiPrompting for bool {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        form.checkbox(name, comment, self);
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl TuiPrompting for std::path::PathBuf {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let mut s = self.display().to_string();
+        if form.text_edit(name, comment, &mut s, false) {
+            *self = std::path::PathBuf::from(s);
+        }
+        Ok(())
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl TuiPrompting for crate::FileOpen {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.pb.build_tui(form, name, comment)?;
+        let r = self.check(name);
+        if let Err(e) = &r {
+            form.error(e);
+        }
+        r
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        self.check_path()
+    }
+}
+
+impl TuiPrompting for crate::FileCreate {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let before = self.pb.clone();
+        self.pb.build_tui(form, name, comment)?;
+        if self.pb != before {
+            self.confirmed = false;
+        }
+        if self.overwrite == crate::Overwrite::Ask && self.pb.is_file() {
+            let subname = format!("{}/replace", name.unwrap_or(""));
+            self.confirmed
+                .build_tui(form, Some(&subname), Some("Replace the existing file"))?;
+        }
+        let r = self.check(name);
+        if let Err(e) = &r {
+            form.error(e);
+        }
+        r
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        self.check_path(self.overwrite)
+    }
+}
+
+/// The text entered is kept while it is being edited, so that glob patterns are only expanded once the user moves on.
+impl TuiPrompting for crate::FilesOpen {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let key = TuiForm::key(name);
+        let mut s = form
+            .buffers
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| self.to_line());
+        let focus = form.count == form.focus;
+        if form.text_edit(name, comment, &mut s, false) {
+            form.buffers.insert(key.clone(), s.clone());
+        }
+        let r = match crate::FilesOpen::parse(&s) {
+            Ok(pbs) => {
+                if !focus {
+                    form.buffers.remove(&key);
+                }
+                self.pbs = pbs;
+                self.check(name)
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &r {
+            form.error(e);
+        }
+        r
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        self.check_files()
+    }
+}
+
+impl TuiPrompting for crate::DirectoryOpen {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.pb.build_tui(form, name, comment)?;
+        let r = self.check(name);
+        if let Err(e) = &r {
+            form.error(e);
+        }
+        r
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        crate::check_directory_open(&self.pb)
+    }
+}
+
+impl TuiPrompting for crate::DirectoryCreate {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.pb.build_tui(form, name, comment)?;
+        let r = self.check(name);
+        if let Err(e) = &r {
+            form.error(e);
+        }
+        r
+    }
+
+    fn check(&self, _name: Option<&str>) -> Result<(), String> {
+        crate::check_directory_create(&self.pb)
+    }
+}
+
+impl TuiPrompting for crate::Password {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        form.text_edit(name, comment, &mut self.0, true);
+        self.check(name)
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        if self.0.is_empty() {
+            return Err(format!("{} password is blank", name.unwrap_or("")));
+        }
+        Ok(())
+    }
+}
+
+impl TuiPrompting for crate::Password2 {
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_tui_with(form, name, comment, &crate::PromptOptions::default())
+    }
+
+    fn build_tui_with(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        form.text_edit(name, comment, &mut self.0, true);
+        form.text_edit(Some("Enter password again"), None, &mut self.1, true);
+        let r = self.check_with(name, options);
+        if let Err(e) = &r {
+            if !self.1.is_empty() {
+                form.error(e);
+            }
+        }
+        r
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        if !self.0.is_empty() && self.0 == self.1 {
+            Ok(())
+        } else {
+            Err(format!("{} password does not match", name.unwrap_or("")))
+        }
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &crate::PromptOptions) -> Result<(), String> {
+        self.check(name)?;
+        self.check_options(name, options)
+    }
+}
+
+impl<T> TuiPrompting for Vec<T>
+where
+    T: TuiPrompting + Default,
+{
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        form.begin_group(name, comment);
+        let mut r = Ok(());
+        let mut remove = None;
+        for (i, e) in self.iter_mut().enumerate() {
+            let name2 = if let Some(n) = name {
+                format!("{}/{}", n, i + 1)
+            } else {
+                format!("{}", i + 1)
+            };
+            let r2 = e.build_tui(form, Some(&name2), None);
+            if r.is_ok() {
+                r = r2;
+            }
+            if form.button(&format!("Remove {}", name2)) {
+                remove = Some(i);
+            }
+        }
+        if let Some(i) = remove {
+            self.remove(i);
+        }
+        if form.button("Add another") {
+            self.push(T::default());
+        }
+        form.end_group();
+        r
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        for i in self {
+            i.check(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> TuiPrompting for Option<T>
+where
+    T: TuiPrompting + Default,
+{
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.build_tui_with(form, name, comment, &crate::PromptOptions::default())
+    }
+
+    fn build_tui_with(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        let mut checked = self.is_some();
+        if form.checkbox(name, None, &mut checked) {
+            *self = if checked { Some(T::default()) } else { None };
+        }
+        match self {
+            Some(thing) => {
+                form.begin_group(None, None);
+                let r = thing.build_tui_with(form, name, comment, options);
+                form.end_group();
+                r
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        self.check_with(name, &crate::PromptOptions::default())
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &crate::PromptOptions) -> Result<(), String> {
+        match self {
+            Some(thing) => thing.check_with(name, options),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> TuiPrompting for Box<T>
+where
+    T: TuiPrompting,
+{
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        self.as_mut().build_tui(form, name, comment)
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        self.as_ref().check(name)
+    }
+
+    fn build_tui_with(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+        options: &crate::PromptOptions,
+    ) -> Result<(), String> {
+        self.as_mut().build_tui_with(form, name, comment, options)
+    }
+
+    fn check_with(&self, name: Option<&str>, options: &crate::PromptOptions) -> Result<(), String> {
+        self.as_ref().check_with(name, options)
+    }
+}
+
+/// Builds the editor for the entries of a map keyed by strings
+fn build_map<T>(
+    map: &mut HashMap<String, T>,
+    form: &mut TuiForm,
+    name: Option<&str>,
+) -> Result<(), String>
+where
+    T: TuiPrompting + Default,
+{
+    let key = TuiForm::key(name);
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    let mut r = Ok(());
+    let mut remove = None;
+    for k in keys {
+        let name2 = format!("{}/{}", key, k);
+        if let Some(e) = map.get_mut(&k) {
+            let r2 = e.build_tui(form, Some(&name2), None);
+            if r.is_ok() {
+                r = r2;
+            }
+        }
+        if form.button(&format!("Remove {}", k)) {
+            remove = Some(k);
+        }
+    }
+    if let Some(k) = remove {
+        map.remove(&k);
+    }
+    let new_key = format!("{}/new", key);
+    let mut new_name = form.buffers.remove(&new_key).unwrap_or_default();
+    form.text_edit(Some("Name for new entry"), None, &mut new_name, false);
+    if form.button("Add new entry") && !new_name.is_empty() && !map.contains_key(&new_name) {
+        map.insert(std::mem::take(&mut new_name), T::default());
+    }
+    form.buffers.insert(new_key, new_name);
+    r
+}
+
+impl<T> TuiPrompting for HashMap<String, T>
+where
+    T: TuiPrompting + Default,
+{
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        form.begin_group(name, comment);
+        let r = build_map(self, form, name);
+        form.end_group();
+        r
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        for a in self.values() {
+            a.check(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> TuiPrompting for crate::SelectedHashMap<T>
+where
+    T: TuiPrompting + Default,
+{
+    fn build_tui(
+        &mut self,
+        form: &mut TuiForm,
+        name: Option<&str>,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        form.begin_group(name, comment);
+        let r = build_map(&mut self.map, form, name);
+        let mut keys = self.keys();
+        keys.insert(0, "");
+        let current = self.selected().unwrap_or_default().to_string();
+        let skey = format!("{}/selection", TuiForm::key(name));
+        if let Some(s) = form.choice(&skey, Some("Selected entry"), None, &keys, &current) {
+            if !self.select(&s) {
+                self.deselect();
+            }
+        }
+        form.end_group();
+        r
+    }
+
+    fn check(&self, name: Option<&str>) -> Result<(), String> {
+        for a in self.map.values() {
+            a.check(name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default, TuiPrompting)]
+    struct Settings {
+        title: String,
+        count: u8,
+        enabled: bool,
+    }
+
+    /// Press a key on a form for the value, returning the result of building it and the action requested
+    fn step<T: TuiPrompting>(
+        form: &mut TuiForm,
+        value: &mut T,
+        code: KeyCode,
+    ) -> (Result<(), String>, Option<Action>) {
+        form.build(value, None, None, Some(KeyEvent::from(code)))
+    }
+
+    /// Press a key on a form for the value
+    fn press<T: TuiPrompting>(form: &mut TuiForm, value: &mut T, code: KeyCode) {
+        let _ = step(form, value, code);
+    }
+
+    /// Type some text into the element with focus
+    fn type_text<T: TuiPrompting>(form: &mut TuiForm, value: &mut T, text: &str) {
+        for c in text.chars() {
+            press(form, value, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn typing_edits_the_focused_field() {
+        let mut form = TuiForm::new();
+        let mut s = Settings::default();
+        type_text(&mut form, &mut s, "abd");
+        press(&mut form, &mut s, KeyCode::Backspace);
+        type_text(&mut form, &mut s, "c");
+        assert_eq!(s.title, "abc");
+        assert_eq!(s.count, 0);
+    }
+
+    #[test]
+    fn navigation_stays_within_the_form() {
+        let mut form = TuiForm::new();
+        let mut s = Settings::default();
+        press(&mut form, &mut s, KeyCode::Up);
+        assert_eq!(form.focus, 0);
+        // Three fields followed by the submit and cancel buttons
+        for _ in 0..10 {
+            press(&mut form, &mut s, KeyCode::Tab);
+        }
+        assert_eq!(form.focus, 4);
+        press(&mut form, &mut s, KeyCode::BackTab);
+        press(&mut form, &mut s, KeyCode::Up);
+        assert_eq!(form.focus, 2);
+        press(&mut form, &mut s, KeyCode::Char(' '));
+        assert!(s.enabled);
+        press(&mut form, &mut s, KeyCode::Enter);
+        assert!(!s.enabled);
+        assert_eq!(form.focus, 2);
+    }
+
+    #[test]
+    fn invalid_text_is_kept_in_its_buffer() {
+        let mut form = TuiForm::new();
+        let mut s = Settings::default();
+        press(&mut form, &mut s, KeyCode::Down);
+        press(&mut form, &mut s, KeyCode::Backspace);
+        type_text(&mut form, &mut s, "42x");
+        assert_eq!(s.count, 42);
+        let (r, _) = step(&mut form, &mut s, KeyCode::Tab);
+        assert!(r.is_err());
+        assert_eq!(form.buffers.get("/count").map(|b| b.as_str()), Some("42x"));
+        press(&mut form, &mut s, KeyCode::BackTab);
+        let (r, _) = step(&mut form, &mut s, KeyCode::Backspace);
+        assert!(r.is_ok());
+        press(&mut form, &mut s, KeyCode::Tab);
+        press(&mut form, &mut s, KeyCode::Tab);
+        assert!(!form.buffers.contains_key("/count"));
+        assert_eq!(s.count, 42);
+    }
+
+    #[test]
+    fn buttons_and_keys_end_the_form() {
+        let mut form = TuiForm::new();
+        let mut s = Settings::default();
+        let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        let (_, action) = form.build(&mut s, None, None, Some(ctrl_s));
+        assert_eq!(action, Some(Action::Submit));
+        assert!(s.title.is_empty());
+        let (_, action) = step(&mut form, &mut s, KeyCode::Esc);
+        assert_eq!(action, Some(Action::Cancel));
+        form.focus = 3;
+        let (_, action) = step(&mut form, &mut s, KeyCode::Enter);
+        assert_eq!(action, Some(Action::Submit));
+        let (_, action) = step(&mut form, &mut s, KeyCode::Tab);
+        assert_eq!(action, None);
+        let (_, action) = step(&mut form, &mut s, KeyCode::Char(' '));
+        assert_eq!(action, Some(Action::Cancel));
+    }
+
+    #[test]
+    fn vec_adds_and_removes_elements() {
+        let mut form = TuiForm::new();
+        let mut v: Vec<u8> = Vec::new();
+        press(&mut form, &mut v, KeyCode::Enter);
+        press(&mut form, &mut v, KeyCode::Enter);
+        assert_eq!(v.len(), 1);
+        // The new element comes before the add button, which keeps its focus
+        assert_eq!(form.focus, 1);
+        form.focus = 0;
+        press(&mut form, &mut v, KeyCode::Backspace);
+        type_text(&mut form, &mut v, "7");
+        assert_eq!(v, vec![7]);
+        press(&mut form, &mut v, KeyCode::Tab);
+        press(&mut form, &mut v, KeyCode::Enter);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn choice_opens_and_selects() {
+        let options = ["red", "green", "blue"];
+        let mut form = TuiForm::new();
+        let choose = |form: &mut TuiForm, code: KeyCode| {
+            form.begin(Some(KeyEvent::from(code)));
+            form.choice("colour", None, None, &options, "red")
+        };
+        assert_eq!(choose(&mut form, KeyCode::Enter), None);
+        assert!(form.open.is_some());
+        assert_eq!(choose(&mut form, KeyCode::Down), None);
+        assert_eq!(choose(&mut form, KeyCode::Down), None);
+        assert_eq!(choose(&mut form, KeyCode::Down), None);
+        assert_eq!(form.open, Some(("colour".to_string(), 2)));
+        assert_eq!(choose(&mut form, KeyCode::Enter), Some("blue".to_string()));
+        assert!(form.open.is_none());
+        choose(&mut form, KeyCode::Char(' '));
+        assert_eq!(choose(&mut form, KeyCode::Esc), None);
+        assert!(form.open.is_none());
+    }
+}
@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
 use syn::{DeriveInput, Ident};
 
-#[cfg(feature = "egui")]
+#[cfg(any(feature = "egui", feature = "html", feature = "tui"))]
 fn build_enum_variant_builder(v: &syn::Variant) -> proc_macro2::TokenStream {
     let sident = proc_macro2::Ident::new("Self", proc_macro2::Span::call_site());
     match &v.fields {
@@ -48,7 +48,7 @@ fn build_enum_variant_builder(v: &syn::Variant) -> proc_macro2::TokenStream {
         syn::Fields::Unnamed(f) => {
             let mut def: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
             let mut tokens: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-            for (i, f) in f.unnamed.iter().enumerate() {
+            for f in f.unnamed.iter() {
                 let ty = &f.ty;
                 tokens.extend(quote::quote!(<#ty as core::default::Default>::default()));
                 tokens.extend([proc_macro2::TokenTree::Punct(proc_macro2::Punct::new(
@@ -97,7 +97,7 @@ fn build_enum_variant_builder(v: &syn::Variant) -> proc_macro2::TokenStream {
 }
 
 /// Builds a match pair for a given enum variant to help convert an enum to a string
-#[cfg(feature = "egui")]
+#[cfg(any(feature = "egui", feature = "html", feature = "tui"))]
 fn build_enum_variant_to_fields(v: &syn::Variant) -> (proc_macro2::TokenStream, Vec<&syn::Field>) {
     let sident = proc_macro2::Ident::new("Self", proc_macro2::Span::call_site());
     let mut fields = Vec::new();
@@ -183,68 +183,18 @@ fn build_enum_variant_to_fields(v: &syn::Variant) -> (proc_macro2::TokenStream,
 }
 
 /// Builds a match pair for a given enum variant to help convert an enum to a string
-#[cfg(feature = "egui")]
+#[cfg(any(feature = "egui", feature = "html", feature = "tui"))]
 fn build_enum_variant_to_string(v: &syn::Variant) -> (proc_macro2::TokenStream, String) {
     let sident = proc_macro2::Ident::new("Self", proc_macro2::Span::call_site());
     let text2 = v.ident.to_string();
     let q: proc_macro2::TokenStream = match &v.fields {
-        syn::Fields::Named(f) => {
-            let mut def: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-            let mut tokens: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-            for f in f.named.iter() {
-                tokens.extend([proc_macro2::TokenTree::Ident(
-                    f.ident.as_ref().unwrap().clone(),
-                )]);
-                tokens.extend([proc_macro2::TokenTree::Punct(proc_macro2::Punct::new(
-                    ',',
-                    proc_macro2::Spacing::Alone,
-                ))]);
-            }
-            def.extend(tokens);
-
-            let mut tokens: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-            tokens.extend([proc_macro2::TokenTree::Ident(sident.clone())]);
-            tokens.extend([
-                proc_macro2::TokenTree::Punct(proc_macro2::Punct::new(
-                    ':',
-                    proc_macro2::Spacing::Joint,
-                )),
-                proc_macro2::TokenTree::Punct(proc_macro2::Punct::new(
-                    ':',
-                    proc_macro2::Spacing::Alone,
-                )),
-            ]);
-            tokens.extend([proc_macro2::TokenTree::Ident(v.ident.clone())]);
-            tokens.extend([proc_macro2::TokenTree::Group(proc_macro2::Group::new(
-                proc_macro2::Delimiter::Brace,
-                def,
-            ))]);
-            tokens
+        syn::Fields::Named(_) => {
+            let vident = &v.ident;
+            quote::quote! {#sident::#vident { .. }}
         }
-        syn::Fields::Unnamed(f) => {
-            let mut tokens: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-            tokens.extend([proc_macro2::TokenTree::Ident(sident.clone())]);
-            tokens.extend([
-                proc_macro2::TokenTree::Punct(proc_macro2::Punct::new(
-                    ':',
-                    proc_macro2::Spacing::Joint,
-                )),
-                proc_macro2::TokenTree::Punct(proc_macro2::Punct::new(
-                    ':',
-                    proc_macro2::Spacing::Alone,
-                )),
-            ]);
-            tokens.extend([proc_macro2::TokenTree::Ident(v.ident.clone())]);
-            let mut tokens2: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-            for (i, _f) in f.unnamed.iter().enumerate() {
-                let varname = quote::format_ident!("a_{}", i);
-                tokens2.extend([proc_macro2::TokenTree::Ident(varname)]);
-                tokens2.extend([proc_macro2::TokenTree::Punct(proc_macro2::Punct::new(
-                    ',',
-                    proc_macro2::Spacing::Alone,
-                ))]);
-            }
-            quote::quote! {#tokens (#tokens2)}
+        syn::Fields::Unnamed(_) => {
+            let vident = &v.ident;
+            quote::quote! {#sident::#vident(..)}
         }
         syn::Fields::Unit => {
             let mut tokens: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
@@ -268,20 +218,30 @@ fn build_enum_variant_to_string(v: &syn::Variant) -> (proc_macro2::TokenStream,
 
 /// This macro is used to drive the EguiPrompting trait for custom types.
 /// The macro attribute PromptComment is used to give direction to the user for each field that the user enters.
+/// Each field is shown with `userprompt::form::field`, so an error is shown next to its field without hiding the rest of the form,
+/// and build_gui returns the first error.
+/// With `#[prompt(layout = "grid")]` on the type each field is a row of a grid, with its name beside it and its comment
+/// when hovering over the name. Fields that are forms of their own, such as structs and lists, are shown in sections that can be collapsed.
+/// The options of the `#[prompt(...)]` attribute are listed in the documentation of [`userprompt::PromptOptions`](https://docs.rs/userprompt/latest/userprompt/struct.PromptOptions.html).
 #[cfg(feature = "egui")]
-#[proc_macro_derive(EguiPrompting, attributes(PromptComment))]
+#[proc_macro_derive(EguiPrompting, attributes(PromptComment, prompt))]
 pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
-    use std::any::Any;
-
     let input = syn::parse_macro_input!(input as DeriveInput);
+    if let Err(e) = check_prompt_attrs(&input) {
+        return e.to_compile_error().into();
+    }
+    let grid = match get_grid_layout(&input.attrs) {
+        Ok(grid) => grid,
+        Err(e) => return e.to_compile_error().into(),
+    };
     let sident = input.ident;
-    let expanded: TokenStream;
-
-    match &input.data {
+    let expanded: TokenStream = match &input.data {
         syn::Data::Enum(e) => {
             let mut field_stuff: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
             let q: proc_macro2::TokenStream = quote::quote! {
-                let combobox = if let Some(name) = name {
+                let combobox = if !userprompt::form::labels(ui, None, None) {
+                    egui::ComboBox::from_id_source(name.unwrap_or("Select"))
+                } else if let Some(name) = name {
                     let mut s = "Select a ".to_string();
                     s.push_str(&name);
                     egui::ComboBox::from_label(s)
@@ -292,7 +252,7 @@ pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
             field_stuff.extend(q);
 
             let mut user_info: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-            for v in &e.variants {
+            for v in e.variants.iter().filter(|_| !grid) {
                 let a = get_comment_from_attrs(&v.attrs);
                 if let Some(a) = a {
                     let (_q, t) = build_enum_variant_to_string(v);
@@ -322,9 +282,12 @@ pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
             for v in &e.variants {
                 let text = v.ident.to_string();
                 let assign = build_enum_variant_builder(v);
-                let t = v.type_id();
+                let hover = match get_comment_from_attrs(&v.attrs).filter(|_| grid) {
+                    Some(a) => quote::quote!(.on_hover_text(#a)),
+                    None => quote::quote!(),
+                };
                 let q: proc_macro2::TokenStream = quote::quote! {
-                    if ui.selectable_label(false, #text).clicked() {
+                    if ui.selectable_label(false, #text)#hover.clicked() {
                         *self = #assign;
                     }
                 };
@@ -333,12 +296,13 @@ pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
 
             let mut option_prompt = proc_macro2::TokenStream::new();
             let mut checks = proc_macro2::TokenStream::new();
+            let mut texts = Vec::new();
             for v in &e.variants {
                 let (q, f) = build_enum_variant_to_fields(v);
                 let a = get_comment_from_attrs(&v.attrs);
                 let mut option_code: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
                 let mut check_item = proc_macro2::TokenStream::new();
-                if let Some(a) = a {
+                if let Some(a) = a.filter(|_| !grid) {
                     let q = quote::quote! {
                         ui.label(#a);
                     };
@@ -346,53 +310,17 @@ pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
                 }
                 if !f.is_empty() {
                     for (i, f) in f.iter().enumerate() {
-                        let a = get_comment(f);
-                        if let Some(ident) = &f.ident {
-                            let varname = quote::format_ident!("{}", ident);
-                            let text = ident.to_string();
-                            let q = match a {
-                                Some(a) => quote::quote! {
-                                    let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                                    #varname.build_gui(ui, Some(&subname), Some(#a))?;
-                                    ui.separator();
-                                },
-                                None => quote::quote! {
-                                    let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                                    #varname.build_gui(ui, Some(&subname), None)?;
-                                    ui.separator();
-                                },
-                            };
-                            option_code.extend(q);
-
-                            let q2 = quote::quote! {
-                                let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                                #varname.check(Some(&subname))?;
-                            };
-                            check_item.extend(q2);
+                        let (varname, text) = if let Some(ident) = &f.ident {
+                            (quote::format_ident!("{}", ident), ident.to_string())
                         } else {
-                            let varname = quote::format_ident!("a_{}", i);
-                            let text = format!("{}", i);
-                            let q = match a {
-                                Some(a) => quote::quote! {
-                                    let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                                    #varname.build_gui(ui, Some(&subname), Some(#a))?;
-                                    ui.separator();
-                                },
-                                None => quote::quote! {
-                                    let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                                    #varname.build_gui(ui, Some(&subname), None)?;
-                                    ui.separator();
-                                },
-                            };
-                            option_code.extend(q);
-
-                            let q2 = quote::quote! {
-                                let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                                #varname.check(Some(&subname))?;
-                            };
-                            check_item.extend(q2);
-                        }
+                            (quote::format_ident!("a_{}", i), format!("{}", i))
+                        };
+                        texts.push(text.clone());
+                        let (q, q2) = build_egui_field(f, quote::quote!(#varname), text, grid);
+                        option_code.extend(q);
+                        check_item.extend(q2);
                     }
+                    let option_code = build_egui_grid(grid, option_code);
                     option_prompt.extend(quote::quote! {
                         #q => { #option_code },
                     });
@@ -402,19 +330,27 @@ pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
                 }
             }
 
-            expanded = quote::quote! {
+            let nested = !texts.is_empty();
+            quote::quote! {
                 impl userprompt::EguiPrompting for #sident {
                     fn build_gui(&mut self, ui: &mut egui::Ui, name: Option<&str>, comment: Option<&str>) -> Result<(), String> {
                         #field_stuff
                         combobox.selected_text(val)
                             .show_ui(ui, |ui| { #combo_stuff });
+                        #[allow(unused_mut)]
+                        let mut result: Result<(), String> = Ok(());
                         match self {
                             #option_prompt
                             _ => {}
                         }
+                        result?;
                         self.check(name)
                     }
 
+                    fn is_nested_form(&self) -> bool {
+                        #nested
+                    }
+
                     fn check(&self, name: Option<&str>) -> Result<(), String> {
                         match self {
                             #checks
@@ -424,46 +360,35 @@ pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
                     }
                 }
             }
-            .into();
+            .into()
         }
         syn::Data::Struct(s) => {
+            if let Err(e) = require_named_fields(&sident, &s.fields) {
+                return e;
+            }
             let fields = &s.fields;
             let mut field_stuff: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
             let mut checks = proc_macro2::TokenStream::new();
 
             let q: proc_macro2::TokenStream = quote::quote! {
-                if let Some(name) = name {
-                    ui.label(name);
-                }
+                userprompt::form::labels(ui, name, None);
+                #[allow(unused_mut)]
+                let mut result: Result<(), String> = Ok(());
             };
             field_stuff.extend(q);
 
             if let syn::Fields::Named(n) = fields {
+                let mut rows = proc_macro2::TokenStream::new();
                 for n in n.named.iter() {
-                    let a = get_comment(n);
                     if let Some(ident) = &n.ident {
                         let text = ident.to_string();
                         let varname = quote::format_ident!("{}", ident);
-                        let q = match a {
-                            Some(a) => quote::quote! {
-                                let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                                self.#varname.build_gui(ui, Some(&subname), Some(#a))?;
-                                ui.separator();
-                            },
-                            None => quote::quote! {
-                                let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                                self.#varname.build_gui(ui, Some(&subname), None)?;
-                                ui.separator();
-                            },
-                        };
-                        field_stuff.extend(q);
-                        let q2 = quote::quote! {
-                            let subname = format!("{}/{}", name.unwrap_or(""), #text);
-                            self.#varname.check(Some(&subname))?;
-                        };
+                        let (q, q2) = build_egui_field(n, quote::quote!(self.#varname), text, grid);
+                        rows.extend(q);
                         checks.extend(q2);
                     }
                 }
+                field_stuff.extend(build_egui_grid(grid, rows));
 
                 let mut q2s: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
 
@@ -478,11 +403,11 @@ pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
                 }
 
                 let q: proc_macro2::TokenStream = quote::quote! {
-                    Ok(())
+                    result
                 };
                 field_stuff.extend(q);
             }
-            expanded = quote::quote! {
+            quote::quote! {
                 impl userprompt::EguiPrompting for #sident {
                     fn build_gui(&mut self, ui: &mut egui::Ui, name: Option<&str>, comment: Option<&str>) -> Result<(), String> {
                         #field_stuff
@@ -492,13 +417,267 @@ pub fn derive_egui_prompting(input: TokenStream) -> TokenStream {
                         #checks
                         Ok(())
                     }
-                }                
+
+                    fn is_nested_form(&self) -> bool {
+                        true
+                    }
+                }
             }
-            .into();
+            .into()
         }
         _ => panic!("Unhandled object type"),
     };
-    TokenStream::from(expanded)
+    expanded
+}
+
+/// The paths used by a form based derive to refer to the trait being derived
+#[cfg(any(feature = "html", feature = "tui"))]
+struct FormTrait {
+    /// The path of the trait
+    path: proc_macro2::TokenStream,
+    /// The name of the function that builds the form for an object
+    build: proc_macro2::TokenStream,
+    /// The name of the function that builds the form for an object with options
+    build_with: proc_macro2::TokenStream,
+    /// The path of the form type
+    form: proc_macro2::TokenStream,
+}
+
+/// Builds the code to render a single field in a form, and the code to check the field
+#[cfg(any(feature = "html", feature = "tui"))]
+fn build_form_field(
+    t: &FormTrait,
+    f: &syn::Field,
+    access: proc_macro2::TokenStream,
+    access_ref: proc_macro2::TokenStream,
+    subname: proc_macro2::TokenStream,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let comment = match get_comment(f) {
+        Some(a) => quote::quote!(Some(#a)),
+        None => quote::quote!(None),
+    };
+    let (path, build_fn, build_with) = (&t.path, &t.build, &t.build_with);
+    // Mistakes in the attribute are reported by check_prompt_attrs before any field is built
+    let (build, check) = match get_prompt_attr(&f.attrs).unwrap_or_default().options() {
+        Some(o) => (
+            quote::quote!(#path::#build_with(#access, form, Some(&subname), #comment, &#o)),
+            quote::quote!(#path::check_with(#access_ref, Some(&subname), &#o)?;),
+        ),
+        None => (
+            quote::quote!(#path::#build_fn(#access, form, Some(&subname), #comment)),
+            quote::quote!(#path::check(#access_ref, Some(&subname))?;),
+        ),
+    };
+    let build = quote::quote! {
+        {
+            let subname = #subname;
+            let r = #build;
+            if result.is_ok() {
+                result = r;
+            }
+        }
+    };
+    let check = quote::quote! {
+        {
+            let subname = #subname;
+            #check
+        }
+    };
+    (build, check)
+}
+
+/// This macro is used to derive the HtmlPrompting trait for custom types.
+/// The macro attribute PromptComment is used to give direction to the user for each field that the user enters.
+/// The options of the `#[prompt(...)]` attribute are listed in the documentation of [`userprompt::PromptOptions`](https://docs.rs/userprompt/latest/userprompt/struct.PromptOptions.html).
+#[cfg(feature = "html")]
+#[proc_macro_derive(HtmlPrompting, attributes(PromptComment, prompt))]
+pub fn derive_html_prompting(input: TokenStream) -> TokenStream {
+    let t = FormTrait {
+        path: quote::quote!(userprompt::html::HtmlPrompting),
+        build: quote::quote!(build_html),
+        build_with: quote::quote!(build_html_with),
+        form: quote::quote!(userprompt::html::HtmlForm),
+    };
+    derive_form_prompting(input, t)
+}
+
+/// This macro is used to derive the TuiPrompting trait for custom types.
+/// The macro attribute PromptComment is used to give direction to the user for each field that the user enters.
+/// The options of the `#[prompt(...)]` attribute are listed in the documentation of [`userprompt::PromptOptions`](https://docs.rs/userprompt/latest/userprompt/struct.PromptOptions.html).
+#[cfg(feature = "tui")]
+#[proc_macro_derive(TuiPrompting, attributes(PromptComment, prompt))]
+pub fn derive_tui_prompting(input: TokenStream) -> TokenStream {
+    let t = FormTrait {
+        path: quote::quote!(userprompt::tui::TuiPrompting),
+        build: quote::quote!(build_tui),
+        build_with: quote::quote!(build_tui_with),
+        form: quote::quote!(userprompt::tui::TuiForm),
+    };
+    derive_form_prompting(input, t)
+}
+
+/// Derives one of the traits that build a form from a series of elements, like the html and terminal forms.
+#[cfg(any(feature = "html", feature = "tui"))]
+fn derive_form_prompting(input: TokenStream, t: FormTrait) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    if let Err(e) = check_prompt_attrs(&input) {
+        return e.to_compile_error().into();
+    }
+    let sident = input.ident;
+    let (path, build_fn, form_ty) = (&t.path, &t.build, &t.form);
+    let expanded: TokenStream = match &input.data {
+        syn::Data::Enum(e) => {
+            let mut variant_names = Vec::new();
+            let mut user_info = proc_macro2::TokenStream::new();
+            let mut match_stuff = proc_macro2::TokenStream::new();
+            let mut assign_stuff = proc_macro2::TokenStream::new();
+            for v in &e.variants {
+                let (q, t) = build_enum_variant_to_string(v);
+                if let Some(a) = get_comment_from_attrs(&v.attrs) {
+                    user_info.extend(quote::quote! {
+                        form.text(&format!("{} - {}", #t, #a));
+                    });
+                }
+                match_stuff.extend(quote::quote! {
+                    #q => #t,
+                });
+                let assign = build_enum_variant_builder(v);
+                assign_stuff.extend(quote::quote! {
+                    #t => *self = #assign,
+                });
+                variant_names.push(t);
+            }
+
+            let mut option_prompt = proc_macro2::TokenStream::new();
+            let mut checks = proc_macro2::TokenStream::new();
+            for v in &e.variants {
+                let (q, f) = build_enum_variant_to_fields(v);
+                if f.is_empty() {
+                    continue;
+                }
+                let vtext = v.ident.to_string();
+                let mut option_code = proc_macro2::TokenStream::new();
+                let mut check_item = proc_macro2::TokenStream::new();
+                for (i, f) in f.iter().enumerate() {
+                    let (varname, text) = match &f.ident {
+                        Some(ident) => (quote::format_ident!("{}", ident), ident.to_string()),
+                        None => (quote::format_ident!("a_{}", i), format!("{}", i)),
+                    };
+                    let subname =
+                        quote::quote!(format!("{}/{}/{}", name.unwrap_or(""), #vtext, #text));
+                    let (build, check) = build_form_field(
+                        &t,
+                        f,
+                        quote::quote!(#varname),
+                        quote::quote!(#varname),
+                        subname,
+                    );
+                    option_code.extend(build);
+                    check_item.extend(check);
+                }
+                option_prompt.extend(quote::quote! {
+                    #q => {
+                        form.begin_group(None, None);
+                        #option_code
+                        form.end_group();
+                    },
+                });
+                checks.extend(quote::quote! {
+                    #q => { #check_item },
+                });
+            }
+
+            quote::quote! {
+                impl #path for #sident {
+                    fn #build_fn(&mut self, form: &mut #form_ty, name: Option<&str>, comment: Option<&str>) -> Result<(), String> {
+                        let key = #form_ty::key(name);
+                        let current = match self {
+                            #match_stuff
+                        };
+                        #user_info
+                        if let Some(s) = form.choice(&key, name, comment, &[#(#variant_names),*], current) {
+                            match s.as_str() {
+                                #assign_stuff
+                                _ => {}
+                            }
+                        }
+                        let mut result = Ok(());
+                        match self {
+                            #option_prompt
+                            _ => {}
+                        }
+                        result
+                    }
+
+                    fn check(&self, name: Option<&str>) -> Result<(), String> {
+                        match self {
+                            #checks
+                            _ => {}
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            .into()
+        }
+        syn::Data::Struct(s) => {
+            if let Err(e) = require_named_fields(&sident, &s.fields) {
+                return e;
+            }
+            let mut field_stuff = proc_macro2::TokenStream::new();
+            let mut checks = proc_macro2::TokenStream::new();
+            if let syn::Fields::Named(n) = &s.fields {
+                for n in n.named.iter() {
+                    if let Some(ident) = &n.ident {
+                        let text = ident.to_string();
+                        let subname = quote::quote!(format!("{}/{}", name.unwrap_or(""), #text));
+                        let (build, check) = build_form_field(
+                            &t,
+                            n,
+                            quote::quote!(&mut self.#ident),
+                            quote::quote!(&self.#ident),
+                            subname,
+                        );
+                        field_stuff.extend(build);
+                        checks.extend(check);
+                    }
+                }
+            }
+            quote::quote! {
+                impl #path for #sident {
+                    fn #build_fn(&mut self, form: &mut #form_ty, name: Option<&str>, comment: Option<&str>) -> Result<(), String> {
+                        form.begin_group(name, comment);
+                        let mut result = Ok(());
+                        #field_stuff
+                        form.end_group();
+                        result
+                    }
+
+                    fn check(&self, name: Option<&str>) -> Result<(), String> {
+                        #checks
+                        Ok(())
+                    }
+                }
+            }
+            .into()
+        }
+        _ => panic!("Unhandled object type"),
+    };
+    expanded
+}
+
+/// Returns a compile error for a struct that does not have named fields, as the derives can only
+/// build a form or prompt for each named field
+fn require_named_fields(sident: &Ident, fields: &syn::Fields) -> Result<(), TokenStream> {
+    match fields {
+        syn::Fields::Named(_) => Ok(()),
+        _ => Err(syn::Error::new_spanned(
+            sident,
+            "only structs with named fields are supported",
+        )
+        .to_compile_error()
+        .into()),
+    }
 }
 
 /// Retrieves the comment attribute from the field
@@ -507,7 +686,7 @@ fn get_comment(field: &syn::Field) -> Option<syn::Expr> {
 }
 
 /// Retrieves the comment attribute from a list of attributes
-fn get_comment_from_attrs(attrs: &Vec<syn::Attribute>) -> Option<syn::Expr> {
+fn get_comment_from_attrs(attrs: &[syn::Attribute]) -> Option<syn::Expr> {
     attrs
         .iter()
         .filter(|p| p.path().is_ident("PromptComment"))
@@ -516,14 +695,329 @@ fn get_comment_from_attrs(attrs: &Vec<syn::Attribute>) -> Option<syn::Expr> {
         .map(|a| a.meta.require_name_value().unwrap().value.clone())
 }
 
+/// The options given to a field with the prompt attribute
+#[derive(Default)]
+struct PromptAttr {
+    /// The number of seconds to wait for the field before using its default value
+    timeout_secs: Option<syn::LitInt>,
+    /// The fields of the PromptOptions given for the field
+    options: proc_macro2::TokenStream,
+    /// Show a button to duplicate each element of a list in egui forms
+    duplicate: bool,
+}
+
+impl PromptAttr {
+    /// Builds the expression for the PromptOptions of the field, if any options were given
+    fn options(&self) -> Option<proc_macro2::TokenStream> {
+        let fields = &self.options;
+        if fields.is_empty() {
+            None
+        } else {
+            // The struct updates are kept even when every field is given, so that the code does not depend on the number of fields
+            Some(quote::quote! {
+                {
+                    #[allow(clippy::needless_update)]
+                    let options = userprompt::PromptOptions { #fields ..Default::default() };
+                    options
+                }
+            })
+        }
+    }
+}
+
+/// Parses the contents of policy(...) into the fields of a password policy
+fn parse_policy(meta: syn::meta::ParseNestedMeta) -> syn::Result<proc_macro2::TokenStream> {
+    let mut fields = proc_macro2::TokenStream::new();
+    meta.parse_nested_meta(|meta| {
+        let flags = [
+            "require_lowercase",
+            "require_uppercase",
+            "require_digit",
+            "require_symbol",
+            "reject_common",
+        ];
+        if meta.path.is_ident("min_length") {
+            let v: syn::LitInt = meta.value()?.parse()?;
+            fields.extend(quote::quote!(min_length: #v,));
+        } else if meta.path.is_ident("min_entropy") {
+            let v: syn::Lit = meta.value()?.parse()?;
+            fields.extend(quote::quote!(min_entropy: Some(#v as f64),));
+        } else if let Some(flag) = flags.iter().find(|f| meta.path.is_ident(f)) {
+            let ident = Ident::new(flag, proc_macro2::Span::call_site());
+            fields.extend(quote::quote!(#ident: true,));
+        } else {
+            return Err(meta.error("unsupported password policy option"));
+        }
+        Ok(())
+    })?;
+    Ok(fields)
+}
+
+/// Parses the prompt attribute of every field, so that a mistake in any of them is reported as a compile error,
+/// with all of the mistakes combined, before the fields are built
+fn check_prompt_attrs(input: &DeriveInput) -> syn::Result<()> {
+    let fields: Vec<&syn::Field> = match &input.data {
+        syn::Data::Struct(s) => s.fields.iter().collect(),
+        syn::Data::Enum(e) => e.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        syn::Data::Union(_) => Vec::new(),
+    };
+    let mut errors: Option<syn::Error> = None;
+    for f in fields {
+        if let Err(e) = get_prompt_attr(&f.attrs) {
+            match &mut errors {
+                Some(all) => all.combine(e),
+                None => errors = Some(e),
+            }
+        }
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+/// Retrieves the options of the prompt attribute from a list of attributes
+fn get_prompt_attr(attrs: &[syn::Attribute]) -> syn::Result<PromptAttr> {
+    let mut p = PromptAttr::default();
+    for a in attrs.iter().filter(|a| a.path().is_ident("prompt")) {
+        a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("timeout_secs") {
+                p.timeout_secs = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("duplicate") {
+                p.duplicate = true;
+                Ok(())
+            } else if meta.path.is_ident("policy") {
+                let policy = parse_policy(meta)?;
+                p.options.extend(quote::quote! {
+                    password_policy: Some(userprompt::PasswordPolicy { #policy ..Default::default() }),
+                });
+                Ok(())
+            } else if meta.path.is_ident("mask") {
+                let v: syn::LitChar = meta.value()?.parse()?;
+                p.options.extend(quote::quote!(mask: Some(#v),));
+                Ok(())
+            } else if meta.path.is_ident("reveal") {
+                p.options.extend(quote::quote!(reveal: true,));
+                Ok(())
+            } else if let Some(ident) = ["min", "max", "step"].iter().find(|i| meta.path.is_ident(i)) {
+                let ident = Ident::new(ident, proc_macro2::Span::call_site());
+                let v: syn::Expr = meta.value()?.parse()?;
+                p.options.extend(quote::quote!(#ident: Some((#v) as f64),));
+                Ok(())
+            } else if meta.path.is_ident("unit") {
+                let v: syn::LitStr = meta.value()?.parse()?;
+                p.options.extend(quote::quote!(unit: Some(#v.to_string()),));
+                Ok(())
+            } else if meta.path.is_ident("schemes") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let v = content.parse_terminated(<syn::LitStr as syn::parse::Parse>::parse, syn::Token![,])?;
+                let v = v.iter();
+                p.options.extend(quote::quote!(schemes: vec![#(#v.to_string()),*],));
+                Ok(())
+            } else if meta.path.is_ident("default_port") {
+                let v: syn::LitInt = meta.value()?.parse()?;
+                p.options.extend(quote::quote!(default_port: Some(#v),));
+                Ok(())
+            } else if meta.path.is_ident("overwrite") {
+                let v: syn::LitStr = meta.value()?.parse()?;
+                let o = match v.value().as_str() {
+                    "reject" => quote::quote!(Reject),
+                    "ask" => quote::quote!(Ask),
+                    "allow" => quote::quote!(Allow),
+                    _ => return Err(syn::Error::new(v.span(), "overwrite must be \"reject\", \"ask\" or \"allow\"")),
+                };
+                p.options.extend(quote::quote!(overwrite: Some(userprompt::Overwrite::#o),));
+                Ok(())
+            } else if meta.path.is_ident("enforce_extension") {
+                p.options.extend(quote::quote!(enforce_extension: true,));
+                Ok(())
+            } else if meta.path.is_ident("validate") {
+                let v: syn::Path = meta.value()?.parse()?;
+                p.options.extend(quote::quote!(validator: Some(userprompt::FileValidator::new(#v)),));
+                Ok(())
+            } else if meta.path.is_ident("filter") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let desc: syn::LitStr = content.parse()?;
+                content.parse::<syn::Token![,]>()?;
+                let list;
+                syn::bracketed!(list in content);
+                let exts = list.parse_terminated(<syn::LitStr as syn::parse::Parse>::parse, syn::Token![,])?;
+                let exts = exts.iter();
+                p.options.extend(quote::quote!(filter: Some((#desc.to_string(), vec![#(#exts.to_string()),*])),));
+                Ok(())
+            } else if let Some(ident) = ["title", "initial_file"].iter().find(|i| meta.path.is_ident(i)) {
+                let ident = Ident::new(ident, proc_macro2::Span::call_site());
+                let v: syn::LitStr = meta.value()?.parse()?;
+                p.options.extend(quote::quote!(#ident: Some(#v.to_string()),));
+                Ok(())
+            } else if let Some(ident) = ["initial_dir", "base_dir"].iter().find(|i| meta.path.is_ident(i)) {
+                let ident = Ident::new(ident, proc_macro2::Span::call_site());
+                let v: syn::LitStr = meta.value()?.parse()?;
+                p.options.extend(quote::quote!(#ident: Some(userprompt::path::expand(#v).into()),));
+                Ok(())
+            } else if meta.path.is_ident("canonicalize") {
+                p.options.extend(quote::quote!(canonicalize: true,));
+                Ok(())
+            } else if let Some(ident) = ["min_len", "max_len"].iter().find(|i| meta.path.is_ident(i)) {
+                let ident = Ident::new(ident, proc_macro2::Span::call_site());
+                let v: syn::LitInt = meta.value()?.parse()?;
+                p.options.extend(quote::quote!(#ident: Some(#v),));
+                Ok(())
+            } else {
+                Err(meta.error("unsupported prompt option"))
+            }
+        })?;
+    }
+    Ok(p)
+}
+
+/// Builds the code to prompt for a single field on the command line
+fn build_prompt_field(f: &syn::Field, text: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let ftype = &f.ty;
+    let comment = match get_comment(f) {
+        Some(a) => quote::quote!(Some(#a)),
+        None => quote::quote!(None),
+    };
+    // Mistakes in the attribute are reported by check_prompt_attrs before any field is built
+    let attr = get_prompt_attr(&f.attrs).unwrap_or_default();
+    let prompt = match attr.options() {
+        Some(o) => quote::quote! {
+            <#ftype as userprompt::Prompting>::prompt_with(Some(#text), #comment, &#o)
+        },
+        None => quote::quote! {
+            <#ftype as userprompt::Prompting>::prompt(Some(#text), #comment)
+        },
+    };
+    match attr.timeout_secs {
+        Some(t) => quote::quote! {
+            userprompt::timeout::or_default(std::time::Duration::from_secs(#t), || #prompt)?
+        },
+        None => quote::quote!(#prompt?),
+    }
+}
+
+/// Builds the code to show a single field in an egui form, and the code to check the field.
+/// The first error of the fields is kept in result, so that the rest of the form is still shown.
+/// In a grid layout the field is a row of the grid made by build_egui_grid, with a section beside its label if it is a form of its own.
+#[cfg(feature = "egui")]
+fn build_egui_field(
+    f: &syn::Field,
+    access: proc_macro2::TokenStream,
+    text: String,
+    grid: bool,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let comment = match get_comment(f) {
+        Some(a) => quote::quote!(Some(#a)),
+        None => quote::quote!(None),
+    };
+    // Mistakes in the attribute are reported by check_prompt_attrs before any field is built
+    let attr = get_prompt_attr(&f.attrs).unwrap_or_default();
+    let options = attr.options();
+    let check = match &options {
+        Some(o) => quote::quote!(#access.check_with(Some(&subname), &#o)?;),
+        None => quote::quote!(#access.check(Some(&subname))?;),
+    };
+    // A list that can duplicate its elements is built through the EguiDuplicate trait, which requires elements that can be cloned
+    let build_with = |comment: &proc_macro2::TokenStream| {
+        if attr.duplicate {
+            let o = options
+                .clone()
+                .unwrap_or_else(|| quote::quote!(userprompt::PromptOptions::default()));
+            quote::quote! {
+                {
+                    use userprompt::EguiDuplicate as _;
+                    #access.build_gui_duplicate(ui, Some(&subname), #comment, &#o)
+                }
+            }
+        } else {
+            match &options {
+                Some(o) => quote::quote!(#access.build_gui_with(ui, Some(&subname), #comment, &#o)),
+                None => quote::quote!(#access.build_gui(ui, Some(&subname), #comment)),
+            }
+        }
+    };
+    let build = build_with(&comment);
+    let show = if grid {
+        let build = build_with(&quote::quote!(None));
+        quote::quote! {
+            let r = if #access.is_nested_form() {
+                userprompt::form::grid_section(ui, &subname, #text, #comment, |ui| #build)
+            } else {
+                userprompt::form::grid_row(ui, &subname, #text, #comment, |ui| #build)
+            };
+            ui.end_row();
+            if result.is_ok() {
+                result = r;
+            }
+        }
+    } else {
+        quote::quote! {
+            let r = userprompt::form::field(ui, &subname, |ui| #build);
+            if result.is_ok() {
+                result = r;
+            }
+            ui.separator();
+        }
+    };
+    (
+        quote::quote! {
+            let subname = format!("{}/{}", name.unwrap_or(""), #text);
+            #show
+        },
+        quote::quote! {
+            let subname = format!("{}/{}", name.unwrap_or(""), #text);
+            #check
+        },
+    )
+}
+
+/// Builds the code to show the fields of a form in a grid layout, a single grid with a row for each field
+#[cfg(feature = "egui")]
+fn build_egui_grid(grid: bool, fields: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if grid {
+        quote::quote! {
+            egui::Grid::new(("userprompt grid", name))
+                .num_columns(2)
+                .show(ui, |ui| { #fields });
+        }
+    } else {
+        fields
+    }
+}
+
+/// Returns true if the prompt attribute of a type asks for the grid layout
+#[cfg(feature = "egui")]
+fn get_grid_layout(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut grid = false;
+    for a in attrs.iter().filter(|a| a.path().is_ident("prompt")) {
+        a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("layout") {
+                let v: syn::LitStr = meta.value()?.parse()?;
+                grid = match v.value().as_str() {
+                    "grid" => true,
+                    "vertical" => false,
+                    _ => return Err(syn::Error::new(v.span(), "layout must be \"grid\" or \"vertical\"")),
+                };
+                Ok(())
+            } else {
+                Err(meta.error("unsupported prompt option for a type"))
+            }
+        })?;
+    }
+    Ok(grid)
+}
+
 /// This macro is used to drive the Prompting trait for custom types.
 /// The macro attribute PromptComment is used to give direction to the user for each field that the user enters.
-#[proc_macro_derive(Prompting, attributes(PromptComment))]
+/// The options of the `#[prompt(...)]` attribute are listed in the documentation of [`userprompt::PromptOptions`](https://docs.rs/userprompt/latest/userprompt/struct.PromptOptions.html).
+#[proc_macro_derive(Prompting, attributes(PromptComment, prompt))]
 pub fn derive_prompting(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
+    if let Err(e) = check_prompt_attrs(&input) {
+        return e.to_compile_error().into();
+    }
     let sident = input.ident;
-    let expanded: TokenStream;
-    match &input.data {
+    let expanded: TokenStream = match &input.data {
         syn::Data::Enum(e) => {
             let mut field_stuff: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
             let q: proc_macro2::TokenStream = quote::quote! {
@@ -552,7 +1046,7 @@ pub fn derive_prompting(input: TokenStream) -> TokenStream {
                 field_stuff.extend(q);
             }
 
-            let name = Ident::new(&format!("a"), proc_macro2::Span::call_site());
+            let name = Ident::new("a", proc_macro2::Span::call_site());
             let q: proc_macro2::TokenStream = quote::quote! {
                 let #name = <String as userprompt::Prompting>::prompt(None, None)?;
             };
@@ -571,20 +1065,11 @@ pub fn derive_prompting(input: TokenStream) -> TokenStream {
                                     proc_macro2::Punct::new(',', proc_macro2::Spacing::Alone),
                                 )]);
                             }
-                            let a = get_comment(f);
                             tokens.extend([proc_macro2::TokenTree::Ident(
                                 f.ident.as_ref().unwrap().clone(),
                             )]);
-                            let ftype = &f.ty;
                             let text = f.ident.as_ref().unwrap().to_string();
-                            let val = match a {
-                                Some(a) => {
-                                    quote::quote!(<#ftype as userprompt::Prompting>::prompt(Some(#text), Some(#a))?)
-                                }
-                                None => {
-                                    quote::quote!(<#ftype as userprompt::Prompting>::prompt(Some(#text), None)?)
-                                }
-                            };
+                            let val = build_prompt_field(f, quote::quote!(#text));
                             tokens.extend([proc_macro2::TokenTree::Punct(
                                 proc_macro2::Punct::new(':', proc_macro2::Spacing::Alone),
                             )]);
@@ -625,16 +1110,7 @@ pub fn derive_prompting(input: TokenStream) -> TokenStream {
                             tokens.extend([proc_macro2::TokenTree::Literal(
                                 proc_macro2::Literal::usize_unsuffixed(i),
                             )]);
-                            let a = get_comment(f);
-                            let ftype = &f.ty;
-                            let val = match a {
-                                Some(a) => {
-                                    quote::quote!(<#ftype as userprompt::Prompting>::prompt(Some(&format!("{}", #i)), Some(#a))?)
-                                }
-                                None => {
-                                    quote::quote!(<#ftype as userprompt::Prompting>::prompt(Some(&format!("{}", #i)), None)?)
-                                }
-                            };
+                            let val = build_prompt_field(f, quote::quote!(&format!("{}", #i)));
                             tokens.extend([proc_macro2::TokenTree::Punct(
                                 proc_macro2::Punct::new(':', proc_macro2::Spacing::Alone),
                             )]);
@@ -696,7 +1172,7 @@ pub fn derive_prompting(input: TokenStream) -> TokenStream {
             };
             field_stuff.extend(q_start);
 
-            expanded = quote::quote! {
+            quote::quote! {
                 impl userprompt::Prompting for #sident {
                     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, userprompt::Error> {
                         loop {
@@ -705,9 +1181,12 @@ pub fn derive_prompting(input: TokenStream) -> TokenStream {
                     }
                 }
             }
-            .into();
+            .into()
         }
         syn::Data::Struct(s) => {
+            if let Err(e) = require_named_fields(&sident, &s.fields) {
+                return e;
+            }
             let fields = &s.fields;
             let mut field_stuff: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
 
@@ -720,22 +1199,12 @@ pub fn derive_prompting(input: TokenStream) -> TokenStream {
 
             if let syn::Fields::Named(n) = fields {
                 for (i, n) in n.named.iter().enumerate() {
-                    let ftype = &n.ty;
                     if let Some(ident) = &n.ident {
                         let name = Ident::new(&format!("a_{}", i), proc_macro2::Span::call_site());
                         let text = ident.to_string();
-                        let a = get_comment_from_attrs(&n.attrs);
-                        let q: proc_macro2::TokenStream = match a {
-                            Some(a) => {
-                                quote::quote! {
-                                    let #name = <#ftype as userprompt::Prompting>::prompt(Some(#text), Some(#a))?;
-                                }
-                            }
-                            None => {
-                                quote::quote! {
-                                    let #name = <#ftype as userprompt::Prompting>::prompt(Some(#text), None)?;
-                                }
-                            }
+                        let val = build_prompt_field(n, quote::quote!(#text));
+                        let q: proc_macro2::TokenStream = quote::quote! {
+                            let #name = #val;
                         };
                         field_stuff.extend(q);
                     }
@@ -760,16 +1229,159 @@ pub fn derive_prompting(input: TokenStream) -> TokenStream {
                 };
                 field_stuff.extend(q);
             }
-            expanded = quote::quote! {
+            quote::quote! {
                 impl userprompt::Prompting for #sident {
                     fn prompt(name: Option<&str>, comment: Option<&str>) -> Result<Self, userprompt::Error> {
                         #field_stuff
                     }
                 }
             }
-            .into();
+            .into()
         }
         _ => panic!("Unhandled object type"),
     };
-    TokenStream::from(expanded)
+    expanded
+}
+
+/// Builds the code to prompt for a single field asynchronously
+#[cfg(feature = "async")]
+fn build_async_field(f: &syn::Field, text: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let ftype = &f.ty;
+    let comment = match get_comment(f) {
+        Some(a) => quote::quote!(Some(#a)),
+        None => quote::quote!(None),
+    };
+    // Mistakes in the attribute are reported by check_prompt_attrs before any field is built
+    let attr = get_prompt_attr(&f.attrs).unwrap_or_default();
+    let prompt = match attr.options() {
+        Some(o) => quote::quote! {
+            <#ftype as userprompt::AsyncPrompting>::prompt_async_with(reader, writer, Some(#text), #comment, &#o)
+        },
+        None => quote::quote! {
+            <#ftype as userprompt::AsyncPrompting>::prompt_async(reader, writer, Some(#text), #comment)
+        },
+    };
+    match attr.timeout_secs {
+        Some(t) => quote::quote! {
+            match userprompt::tokio::time::timeout(std::time::Duration::from_secs(#t), #prompt).await {
+                Ok(r) => r?,
+                Err(_) => {
+                    userprompt::asynchronous::write_line(writer, "\nNo input received, using the default value").await?;
+                    <#ftype as Default>::default()
+                }
+            }
+        },
+        None => quote::quote!(#prompt.await?),
+    }
+}
+
+/// This macro is used to derive the AsyncPrompting trait for custom types.
+/// The macro attribute PromptComment is used to give direction to the user for each field that the user enters.
+/// The options of the `#[prompt(...)]` attribute are listed in the documentation of [`userprompt::PromptOptions`](https://docs.rs/userprompt/latest/userprompt/struct.PromptOptions.html).
+#[cfg(feature = "async")]
+#[proc_macro_derive(AsyncPrompting, attributes(PromptComment, prompt))]
+pub fn derive_async_prompting(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    if let Err(e) = check_prompt_attrs(&input) {
+        return e.to_compile_error().into();
+    }
+    let sident = input.ident;
+    let body = match &input.data {
+        syn::Data::Enum(e) => {
+            let mut options = proc_macro2::TokenStream::new();
+            let mut match_stuff = proc_macro2::TokenStream::new();
+            for v in &e.variants {
+                let text = v.ident.to_string();
+                let vident = &v.ident;
+                let q = match get_comment_from_attrs(&v.attrs) {
+                    Some(a) => quote::quote! {
+                        userprompt::asynchronous::write_line(writer, &format!("\t{} - {}", #text, #a)).await?;
+                    },
+                    None => quote::quote! {
+                        userprompt::asynchronous::write_line(writer, &format!("\t{}", #text)).await?;
+                    },
+                };
+                options.extend(q);
+                let q = match &v.fields {
+                    syn::Fields::Named(f) => {
+                        let fields = f.named.iter().map(|f| {
+                            let ident = f.ident.as_ref().unwrap();
+                            let text = ident.to_string();
+                            let val = build_async_field(f, quote::quote!(#text));
+                            quote::quote!(#ident: #val)
+                        });
+                        quote::quote!(Self::#vident { #(#fields),* })
+                    }
+                    syn::Fields::Unnamed(f) => {
+                        let fields = f.unnamed.iter().enumerate().map(|(i, f)| {
+                            build_async_field(f, quote::quote!(&format!("{}", #i)))
+                        });
+                        quote::quote!(Self::#vident ( #(#fields),* ))
+                    }
+                    syn::Fields::Unit => quote::quote!(Self::#vident),
+                };
+                match_stuff.extend(quote::quote! {
+                    #text => { return Ok(#q); }
+                });
+            }
+            quote::quote! {
+                loop {
+                    if let Some(name) = name {
+                        userprompt::asynchronous::write_line(writer, &format!("[{}]", name)).await?;
+                    }
+                    userprompt::asynchronous::write_line(writer, "Enter the variant type, valid options are listed below").await?;
+                    #options
+                    let a = userprompt::asynchronous::read_line(reader).await?;
+                    match a.as_str() {
+                        #match_stuff
+                        _ => userprompt::asynchronous::write_line(writer, "Invalid option").await?,
+                    }
+                }
+            }
+        }
+        syn::Data::Struct(s) => {
+            if let Err(e) = require_named_fields(&sident, &s.fields) {
+                return e;
+            }
+            let mut field_stuff = proc_macro2::TokenStream::new();
+            let mut q2s = proc_macro2::TokenStream::new();
+            if let syn::Fields::Named(n) = &s.fields {
+                for (i, n) in n.named.iter().enumerate() {
+                    if let Some(ident) = &n.ident {
+                        let name = Ident::new(&format!("a_{}", i), proc_macro2::Span::call_site());
+                        let text = ident.to_string();
+                        let val = build_async_field(n, quote::quote!(#text));
+                        field_stuff.extend(quote::quote! {
+                            let #name = #val;
+                        });
+                        q2s.extend(quote::quote! {
+                            #ident: #name,
+                        });
+                    }
+                }
+            }
+            quote::quote! {
+                if let Some(name) = name {
+                    userprompt::asynchronous::write_line(writer, &format!("[{}]", name)).await?;
+                }
+                #field_stuff
+                Ok(Self {
+                    #q2s
+                })
+            }
+        }
+        _ => panic!("Unhandled object type"),
+    };
+    quote::quote! {
+        impl userprompt::AsyncPrompting for #sident {
+            async fn prompt_async<R, W>(reader: &mut R, writer: &mut W, name: Option<&str>, comment: Option<&str>) -> Result<Self, userprompt::Error>
+            where
+                R: userprompt::tokio::io::AsyncRead + Unpin + Send,
+                W: userprompt::tokio::io::AsyncWrite + Unpin + Send,
+            {
+                #body
+            }
+        }
+    }
+    .into()
 }